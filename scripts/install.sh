@@ -65,27 +65,11 @@ print_status "Sistema operacional detectado: $OS"
 
 # Funções para configurar inicialização automática
 setup_autostart_linux() {
-    local autostart_dir="$HOME/.config/autostart"
-    local desktop_file="$autostart_dir/activity-inquirer-daemon.desktop"
+    # Serviço systemd de usuário + entrada XDG autostart, gerenciados pelo próprio executável
+    "$LOCAL_BIN/acv-inq" service install --auto-start
 
-    mkdir -p "$autostart_dir"
-
-    cat > "$desktop_file" << EOF
-[Desktop Entry]
-Type=Application
-Name=Activity Inquirer Daemon
-Comment=Daemon para inquéritos automáticos de atividades
-Exec=$LOCAL_BIN/acv-inq --daemon
-Icon=activity-inquirer
-Terminal=false
-Hidden=false
-X-GNOME-Autostart-enabled=true
-StartupNotify=false
-Categories=Utility;
-EOF
-
-    print_success "Inicialização automática configurada para Linux (XDG autostart)"
-    print_status "Arquivo criado: $desktop_file"
+    print_success "Inicialização automática configurada para Linux (systemd/XDG autostart)"
+    print_status "Para conferir: acv-inq service status"
 }
 
 setup_autostart_macos() {
@@ -104,7 +88,7 @@ setup_autostart_macos() {
     <key>ProgramArguments</key>
     <array>
         <string>$LOCAL_BIN/acv-inq</string>
-        <string>--daemon</string>
+        <string>daemon</string>
     </array>
     <key>RunAtLoad</key>
     <true/>
@@ -136,7 +120,7 @@ setup_autostart_windows() {
     cat > "$batch_file" << EOF
 @echo off
 cd /d "$(dirname "$LOCAL_BIN")"
-start "" "$LOCAL_BIN/acv-inq.exe" --daemon
+start "" "$LOCAL_BIN/acv-inq.exe" daemon
 EOF
 
     print_success "Inicialização automática configurada para Windows"
@@ -145,9 +129,8 @@ EOF
 }
 
 remove_autostart_linux() {
-    local autostart_file="$HOME/.config/autostart/activity-inquirer-daemon.desktop"
-    if [[ -f "$autostart_file" ]]; then
-        rm "$autostart_file"
+    if [[ -x "$LOCAL_BIN/acv-inq" ]]; then
+        "$LOCAL_BIN/acv-inq" service uninstall
         print_success "Inicialização automática removida do Linux"
     fi
 }
@@ -272,7 +255,7 @@ Actions=inquiry;
 [Desktop Action inquiry]
 Name=Fazer Inquérito
 Name[en]=Make Inquiry
-Exec=$LOCAL_BIN/acv-inq --inquiry
+Exec=$LOCAL_BIN/acv-inq inquiry
 EOF
 
 # Tornar o arquivo .desktop executável
@@ -318,7 +301,7 @@ if ask_yes_no "Deseja configurar o daemon para iniciar automaticamente no boot?"
     print_status "Para desabilitar, execute este script novamente ou remova manualmente."
 else
     print_status "Inicialização automática não configurada."
-    print_status "Você pode executar manualmente: acv-inq --daemon"
+    print_status "Você pode executar manualmente: acv-inq daemon"
 fi
 
 echo
@@ -332,8 +315,8 @@ echo
 
 print_status "🎯 Como usar:"
 echo "  1. Modo Visualizador: acv-inq"
-echo "  2. Modo Inquérito: acv-inq --inquiry"
-echo "  3. Modo Daemon: acv-inq --daemon"
+echo "  2. Modo Inquérito: acv-inq inquiry"
+echo "  3. Modo Daemon: acv-inq daemon"
 if [[ "$OS" == "linux" ]]; then
     echo "  4. Menu de aplicações: 'Activity Inquirer'"
 fi
@@ -0,0 +1,634 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde::Serialize;
+use std::io::Write;
+
+use crate::database::Database;
+use crate::hooks::{HookEvent, Hooks};
+use crate::models::{Activity, ActivityInput, DateRange, format_duration};
+use crate::webhooks::Webhooks;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+// Atividade acompanhada da duração calculada, para a saída em JSON
+#[derive(Serialize)]
+pub struct ActivityRecord<'a> {
+    #[serde(flatten)]
+    activity: &'a Activity,
+    duration_seconds: i64,
+}
+
+impl<'a> ActivityRecord<'a> {
+    pub fn new(activity: &'a Activity) -> Self {
+        Self {
+            activity,
+            duration_seconds: activity.duration().num_seconds(),
+        }
+    }
+}
+
+// Registra uma atividade sem abrir a interface gráfica
+pub fn run_log(
+    text: &str,
+    at: Option<&str>,
+    project: Option<&str>,
+    tags: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let (input, at) = prepare_log(text, at, project, tags)?;
+
+    let db = Database::new()?;
+    let hooks = Hooks::load();
+    let webhooks = Webhooks::load();
+    let activity = record_activity(&db, &hooks, &webhooks, &input, at)?;
+    // O comando termina em seguida, então a entrega pode esperar aqui mesmo
+    if let Err(e) = webhooks.deliver_pending(&db, false) {
+        log::warn!("⚠️  {e:#}");
+    }
+    hooks.wait();
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string(&activity).context("Falha ao serializar atividade")?
+        );
+    } else {
+        let mut details = String::new();
+        if let Some(project) = &activity.project {
+            details.push_str(&format!(" @{}", project.name));
+        }
+        for tag in &activity.tags {
+            details.push_str(&format!(" #{tag}"));
+        }
+        println!(
+            "✅ Atividade registrada às {}: \"{}\"{details} (id {})",
+            activity.timestamp.format("%H:%M"),
+            activity.description,
+            activity.id.unwrap_or_default()
+        );
+    }
+
+    Ok(())
+}
+
+// Valida o que foi informado para `log` (ou `POST /activities` no `serve`) e
+// devolve a entrada e o horário de início
+pub fn prepare_log(
+    text: &str,
+    at: Option<&str>,
+    project: Option<&str>,
+    tags: Option<&str>,
+) -> Result<(ActivityInput, DateTime<Local>)> {
+    let mut input = ActivityInput::parse(text);
+    if let Some(project) = project {
+        input.project = Some(project.trim().to_string()).filter(|p| !p.is_empty());
+    }
+    if let Some(tags) = tags {
+        input.add_tags_from_list(tags);
+    }
+    if input.description.is_empty() {
+        bail!("A descrição da atividade não pode ficar vazia");
+    }
+
+    let at = match at {
+        Some(value) => parse_time(value)?,
+        None => Local::now(),
+    };
+    if at > Local::now() {
+        bail!(
+            "Não é possível registrar uma atividade no futuro ({})",
+            at.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    Ok((input, at))
+}
+
+// Grava a atividade, dispara o hook em segundo plano (quem chamou decide se
+// espera por ele com `hooks.wait()`) e enfileira os webhooks
+pub fn record_activity(
+    db: &Database,
+    hooks: &Hooks,
+    webhooks: &Webhooks,
+    input: &ActivityInput,
+    at: DateTime<Local>,
+) -> Result<Activity> {
+    let id = db.add_activity_at(input, at)?;
+    let activity = db
+        .get_activity(id)?
+        .context("Atividade registrada não foi encontrada")?;
+    hooks.run_in_background(HookEvent::ActivitySaved, Some(activity.clone()));
+    webhooks.send(db, HookEvent::ActivitySaved, &activity);
+
+    Ok(activity)
+}
+
+// Imprime as atividades de um dia
+pub fn run_show(date: Option<&str>, format: OutputFormat) -> Result<()> {
+    let date = match date {
+        Some(value) => parse_date(value)?,
+        None => Local::now().date_naive(),
+    };
+
+    let db = Database::new()?;
+    let activities = db.get_activities_for_date(date)?;
+    print_activities(&activities, format)
+}
+
+// Imprime as atividades de um intervalo de datas. Sem filtros, usa a semana atual.
+pub fn run_list(
+    from: Option<&str>,
+    to: Option<&str>,
+    week: Option<&str>,
+    month: Option<&str>,
+    format: OutputFormat,
+) -> Result<()> {
+    let range = parse_range(from, to, week, month)?
+        .unwrap_or_else(|| DateRange::week_of(Local::now().date_naive()));
+
+    let db = Database::new()?;
+    let activities = db.get_activities_between(range)?;
+    print_activities(&activities, format)
+}
+
+fn print_activities(activities: &[Activity], format: OutputFormat) -> Result<()> {
+    let output = match format {
+        OutputFormat::Table => format_table(activities),
+        OutputFormat::Json => {
+            let records: Vec<ActivityRecord> = activities.iter().map(ActivityRecord::new).collect();
+            serde_json::to_string_pretty(&records).context("Falha ao serializar atividades")? + "\n"
+        }
+        OutputFormat::Csv => format_csv(activities),
+    };
+
+    write_stdout(&output)
+}
+
+// Escreve na saída padrão sem entrar em pânico quando o pipe é fechado
+// antes do fim (por exemplo, `acv-inq list | head`)
+fn write_stdout(output: &str) -> Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    match stdout
+        .write_all(output.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => result.context("Falha ao escrever na saída padrão"),
+    }
+}
+
+fn format_table(activities: &[Activity]) -> String {
+    if activities.is_empty() {
+        return "Nenhuma atividade registrada neste período.\n".to_string();
+    }
+
+    let headers = [
+        "DATA",
+        "INÍCIO",
+        "FIM",
+        "DURAÇÃO",
+        "PROJETO",
+        "TAGS",
+        "ATIVIDADE",
+    ];
+    let rows: Vec<[String; 7]> = activities
+        .iter()
+        .map(|activity| {
+            [
+                activity.date.format("%Y-%m-%d").to_string(),
+                activity.timestamp.format("%H:%M").to_string(),
+                activity.end_timestamp.format("%H:%M").to_string(),
+                format_duration(activity.duration()),
+                activity
+                    .project
+                    .as_ref()
+                    .map(|project| project.name.clone())
+                    .unwrap_or_default(),
+                activity
+                    .tags
+                    .iter()
+                    .map(|tag| format!("#{tag}"))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                activity.description.clone(),
+            ]
+        })
+        .collect();
+
+    let mut widths = headers.map(|header| header.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: &[String]| {
+        let line = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| {
+                let padding = width.saturating_sub(cell.chars().count());
+                format!("{cell}{}", " ".repeat(padding))
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("{}\n", line.trim_end())
+    };
+
+    let mut output = format_row(&headers.map(String::from));
+    for row in &rows {
+        output.push_str(&format_row(row));
+    }
+
+    let total = activities
+        .iter()
+        .fold(chrono::Duration::zero(), |total, activity| {
+            total + activity.duration()
+        });
+    output.push_str(&format!(
+        "\n{} registro(s), {} no total\n",
+        activities.len(),
+        format_duration(total)
+    ));
+    output
+}
+
+fn format_csv(activities: &[Activity]) -> String {
+    let mut output = "id,date,start,end,duration_seconds,project,tags,description\n".to_string();
+    for activity in activities {
+        let fields = [
+            activity.id.map(|id| id.to_string()).unwrap_or_default(),
+            activity.date.to_string(),
+            activity.timestamp.to_rfc3339(),
+            activity.end_timestamp.to_rfc3339(),
+            activity.duration().num_seconds().to_string(),
+            activity
+                .project
+                .as_ref()
+                .map(|project| project.name.clone())
+                .unwrap_or_default(),
+            activity.tags.join(";"),
+            activity.description.clone(),
+        ];
+        let line = fields
+            .iter()
+            .map(|field| csv_escape(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// Aceita "AAAA-MM-DD", "hoje"/"today" e "ontem"/"yesterday"
+pub fn parse_date(value: &str) -> Result<NaiveDate> {
+    let today = Local::now().date_naive();
+    match value.trim().to_lowercase().as_str() {
+        "hoje" | "today" => Ok(today),
+        "ontem" | "yesterday" => Ok(today - chrono::Duration::days(1)),
+        other => NaiveDate::parse_from_str(other, "%Y-%m-%d")
+            .with_context(|| format!("Data inválida: \"{value}\". Use AAAA-MM-DD")),
+    }
+}
+
+// Intervalo pedido por --from/--to, --week ou --month; `None` sem nenhum deles.
+// Só --from vai até hoje; só --to é recusado.
+pub fn parse_range(
+    from: Option<&str>,
+    to: Option<&str>,
+    week: Option<&str>,
+    month: Option<&str>,
+) -> Result<Option<DateRange>> {
+    if let Some(week) = week {
+        return parse_week(week).map(Some);
+    }
+    if let Some(month) = month {
+        return parse_month(month).map(Some);
+    }
+
+    let today = Local::now().date_naive();
+    Ok(
+        match (
+            from.map(parse_date).transpose()?,
+            to.map(parse_date).transpose()?,
+        ) {
+            (Some(from), Some(to)) => Some(DateRange::new(from, to)),
+            (Some(from), None) => Some(DateRange::new(from, today)),
+            (None, Some(_)) => bail!("A data final (to) exige uma data inicial (from)"),
+            (None, None) => None,
+        },
+    )
+}
+
+// Semana ISO no formato "AAAA-Www" (ex.: 2024-W05)
+fn parse_week(value: &str) -> Result<DateRange> {
+    let parsed = value
+        .trim()
+        .to_uppercase()
+        .split_once("-W")
+        .and_then(|(year, week)| Some((year.parse::<i32>().ok()?, week.parse::<u32>().ok()?)));
+
+    parsed
+        .and_then(|(year, week)| DateRange::iso_week(year, week))
+        .with_context(|| format!("Semana inválida: \"{value}\". Use AAAA-Www (ex.: 2024-W05)"))
+}
+
+// Mês no formato "AAAA-MM"
+fn parse_month(value: &str) -> Result<DateRange> {
+    let parsed = value
+        .trim()
+        .split_once('-')
+        .and_then(|(year, month)| Some((year.parse::<i32>().ok()?, month.parse::<u32>().ok()?)));
+
+    parsed
+        .and_then(|(year, month)| DateRange::month(year, month))
+        .with_context(|| format!("Mês inválido: \"{value}\". Use AAAA-MM"))
+}
+
+// Aceita "HH:MM" (hoje), "AAAA-MM-DD HH:MM" ou RFC 3339
+pub fn parse_time(value: &str) -> Result<DateTime<Local>> {
+    let value = value.trim();
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Local));
+    }
+
+    let naive = if let Ok(time) = NaiveTime::parse_from_str(value, "%H:%M") {
+        Local::now().date_naive().and_time(time)
+    } else if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M") {
+        naive
+    } else if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M") {
+        naive
+    } else {
+        bail!("Horário inválido: \"{value}\". Use HH:MM, AAAA-MM-DD HH:MM ou RFC 3339");
+    };
+
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .with_context(|| format!("Horário inexistente no fuso horário local: \"{value}\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ActivityKind, Project};
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn range(start: &str, end: &str) -> DateRange {
+        DateRange::new(date(start), date(end))
+    }
+
+    fn activity(
+        start: (u32, u32),
+        end: (u32, u32),
+        description: &str,
+        project: Option<&str>,
+        tags: &[&str],
+    ) -> Activity {
+        let at = |(hour, minute)| Local.with_ymd_and_hms(2024, 2, 1, hour, minute, 0).unwrap();
+        Activity {
+            id: Some(1),
+            description: description.to_string(),
+            timestamp: at(start),
+            end_timestamp: at(end),
+            responses: 1,
+            date: date("2024-02-01"),
+            project: project.map(|name| Project {
+                id: Some(1),
+                name: name.to_string(),
+                color: "#458588".to_string(),
+                icon: None,
+            }),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            kind: ActivityKind::Answered,
+        }
+    }
+
+    #[test]
+    fn range_from_and_to() {
+        let parsed = parse_range(Some("2024-02-01"), Some("2024-02-15"), None, None).unwrap();
+        assert_eq!(parsed, Some(range("2024-02-01", "2024-02-15")));
+
+        // Datas invertidas viram o mesmo intervalo
+        let parsed = parse_range(Some("2024-02-15"), Some("2024-02-01"), None, None).unwrap();
+        assert_eq!(parsed, Some(range("2024-02-01", "2024-02-15")));
+    }
+
+    #[test]
+    fn range_from_alone_goes_until_today() {
+        let today = Local::now().date_naive();
+        let parsed = parse_range(Some("2024-02-01"), None, None, None).unwrap();
+        assert_eq!(parsed, Some(DateRange::new(date("2024-02-01"), today)));
+    }
+
+    #[test]
+    fn range_rejects_to_without_from() {
+        assert!(parse_range(None, Some("2024-02-15"), None, None).is_err());
+    }
+
+    #[test]
+    fn range_without_filters_is_none() {
+        assert_eq!(parse_range(None, None, None, None).unwrap(), None);
+        assert!(parse_range(Some("ontem?"), None, None, None).is_err());
+    }
+
+    #[test]
+    fn range_from_week_and_month() {
+        let parsed = parse_range(None, None, Some("2024-W05"), None).unwrap();
+        assert_eq!(parsed, Some(range("2024-01-29", "2024-02-04")));
+
+        let parsed = parse_range(None, None, None, Some("2024-02")).unwrap();
+        assert_eq!(parsed, Some(range("2024-02-01", "2024-02-29")));
+    }
+
+    #[test]
+    fn week_follows_iso_year() {
+        assert_eq!(
+            parse_week("2024-w05").unwrap(),
+            range("2024-01-29", "2024-02-04")
+        );
+        // A primeira semana de 2025 começa em 2024 e a 53ª de 2020 termina em 2021
+        assert_eq!(
+            parse_week("2025-W01").unwrap(),
+            range("2024-12-30", "2025-01-05")
+        );
+        assert_eq!(
+            parse_week("2020-W53").unwrap(),
+            range("2020-12-28", "2021-01-03")
+        );
+
+        for invalid in ["2021-W53", "2024-W00", "2024-05", "2024-Wxx", ""] {
+            assert!(parse_week(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn month_ends_on_its_last_day() {
+        assert_eq!(
+            parse_month("2023-02").unwrap(),
+            range("2023-02-01", "2023-02-28")
+        );
+        assert_eq!(
+            parse_month("2024-12").unwrap(),
+            range("2024-12-01", "2024-12-31")
+        );
+
+        for invalid in ["2024-13", "2024-00", "2024", "fev-2024"] {
+            assert!(parse_month(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn csv_escape_quotes_only_when_needed() {
+        assert_eq!(csv_escape("revisão de código"), "revisão de código");
+        assert_eq!(csv_escape(""), "");
+        assert_eq!(csv_escape("a, b"), "\"a, b\"");
+        assert_eq!(csv_escape("o \"novo\" layout"), "\"o \"\"novo\"\" layout\"");
+        assert_eq!(csv_escape("linha 1\nlinha 2"), "\"linha 1\nlinha 2\"");
+        assert_eq!(csv_escape("fim\r"), "\"fim\r\"");
+    }
+
+    #[test]
+    fn csv_has_header_and_one_line_per_activity() {
+        let activities = [activity(
+            (9, 0),
+            (10, 30),
+            "planejamento, \"sprint\"",
+            Some("cliente"),
+            &["reunião", "time"],
+        )];
+
+        let csv = format_csv(&activities);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "id,date,start,end,duration_seconds,project,tags,description"
+        );
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].starts_with("1,2024-02-01,"));
+        assert!(lines[1].ends_with(",5400,cliente,reunião;time,\"planejamento, \"\"sprint\"\"\""));
+    }
+
+    #[test]
+    fn table_aligns_columns_and_sums_durations() {
+        let activities = [
+            activity(
+                (9, 0),
+                (10, 30),
+                "planejamento",
+                Some("cliente"),
+                &["reunião"],
+            ),
+            activity((12, 0), (13, 0), "almoço", None, &[]),
+        ];
+
+        let expected = [
+            "DATA        INÍCIO  FIM    DURAÇÃO  PROJETO  TAGS      ATIVIDADE",
+            "2024-02-01  09:00   10:30  1h30m    cliente  #reunião  planejamento",
+            "2024-02-01  12:00   13:00  1h                          almoço",
+            "",
+            "2 registro(s), 2h30m no total",
+            "",
+        ]
+        .join("\n");
+        assert_eq!(format_table(&activities), expected);
+    }
+
+    #[test]
+    fn time_of_day_is_today() {
+        let parsed = parse_time(" 09:15 ").unwrap();
+        assert_eq!(parsed.date_naive(), Local::now().date_naive());
+        assert_eq!(parsed.time(), NaiveTime::from_hms_opt(9, 15, 0).unwrap());
+    }
+
+    #[test]
+    fn absolute_times_are_local() {
+        let expected = Local.with_ymd_and_hms(2024, 2, 1, 10, 30, 0).unwrap();
+        assert_eq!(parse_time("2024-02-01 10:30").unwrap(), expected);
+        assert_eq!(parse_time("2024-02-01T10:30").unwrap(), expected);
+    }
+
+    #[test]
+    fn rfc3339_keeps_the_instant() {
+        let parsed = parse_time("2024-02-01T10:30:00-03:00").unwrap();
+        assert_eq!(parsed.to_utc().to_rfc3339(), "2024-02-01T13:30:00+00:00");
+    }
+
+    #[test]
+    fn invalid_times_are_rejected() {
+        for invalid in ["", "25:00", "9h15", "2024-02-30 10:00", "ontem 10:00"] {
+            assert!(parse_time(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn log_reads_project_and_tags_from_text() {
+        let (input, at) = prepare_log("revisão @cliente #código #pr", None, None, None).unwrap();
+
+        assert_eq!(input.description, "revisão");
+        assert_eq!(input.project.as_deref(), Some("cliente"));
+        assert_eq!(input.tags, ["código", "pr"]);
+        assert!((Local::now() - at).num_seconds().abs() < 5);
+    }
+
+    #[test]
+    fn log_options_override_project_and_add_tags() {
+        let (input, _) = prepare_log(
+            "revisão @cliente #código",
+            None,
+            Some(" interno "),
+            Some("Código, pr"),
+        )
+        .unwrap();
+        assert_eq!(input.project.as_deref(), Some("interno"));
+        // Tags repetidas (sem diferenciar maiúsculas) não são duplicadas
+        assert_eq!(input.tags, ["código", "pr"]);
+
+        let (input, _) = prepare_log("revisão @cliente", None, Some(" "), None).unwrap();
+        assert_eq!(input.project, None);
+    }
+
+    #[test]
+    fn log_uses_given_time() {
+        let (_, at) = prepare_log("reunião", Some("2024-02-01 14:30"), None, None).unwrap();
+        assert_eq!(at, Local.with_ymd_and_hms(2024, 2, 1, 14, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn log_rejects_future_and_empty_description() {
+        let tomorrow = (Local::now() + chrono::Duration::days(2)).format("%Y-%m-%d %H:%M");
+        let result = prepare_log("reunião", Some(&tomorrow.to_string()), None, None);
+        assert!(result.unwrap_err().to_string().contains("futuro"));
+
+        assert!(prepare_log("@cliente #código", None, None, None).is_err());
+        assert!(prepare_log("   ", None, Some("cliente"), None).is_err());
+        assert!(prepare_log("reunião", Some("amanhã"), None, None).is_err());
+    }
+
+    #[test]
+    fn table_without_activities() {
+        assert_eq!(
+            format_table(&[]),
+            "Nenhuma atividade registrada neste período.\n"
+        );
+    }
+}
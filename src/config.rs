@@ -1,13 +1,29 @@
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use chrono::{NaiveDate, NaiveTime, Weekday};
+use croner::Cron;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::options;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub daemon_interval_minutes: u64,
     pub theme: String,
     pub auto_start_daemon: bool,
+    // Tempo que a janela de inquérito fica aberta antes de ser fechada pelo daemon
+    pub inquiry_timeout_minutes: u64,
+    pub unanswered_policy: UnansweredPolicy,
+    pub schedule: ScheduleConfig,
+    pub hooks: HooksConfig,
+    pub webhooks: WebhooksConfig,
+    pub server: ServerConfig,
+    pub prompter: PrompterConfig,
 }
 
 impl Default for AppConfig {
@@ -16,34 +32,397 @@ impl Default for AppConfig {
             daemon_interval_minutes: 60, // 1 hora por padrão
             theme: "GruvboxDark".to_string(),
             auto_start_daemon: false,
+            inquiry_timeout_minutes: 5,
+            unanswered_policy: UnansweredPolicy::default(),
+            schedule: ScheduleConfig::default(),
+            hooks: HooksConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            server: ServerConfig::default(),
+            prompter: PrompterConfig::default(),
+        }
+    }
+}
+
+// Horário de trabalho: o daemon só pergunta dentro das janelas do dia da semana,
+// fora do almoço e fora das datas ignoradas (feriados, férias). Desativado, o
+// daemon pergunta a qualquer hora, como nas versões anteriores.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub mode: ScheduleMode,
+    // Expressão cron de 5 campos (minuto hora dia mês dia-da-semana), usada no modo `cron`
+    pub cron: String,
+    // Modo `sampling`: quantos horários sortear por janela configurada (fora do
+    // almoço) e a distância mínima entre eles
+    pub samples_per_window: u32,
+    pub min_gap_minutes: u64,
+    pub enabled: bool,
+    pub monday: Vec<TimeWindow>,
+    pub tuesday: Vec<TimeWindow>,
+    pub wednesday: Vec<TimeWindow>,
+    pub thursday: Vec<TimeWindow>,
+    pub friday: Vec<TimeWindow>,
+    pub saturday: Vec<TimeWindow>,
+    pub sunday: Vec<TimeWindow>,
+    pub lunch: Option<TimeWindow>,
+    pub skip_dates: Vec<NaiveDate>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        let workday = vec![TimeWindow::new(
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        )];
+
+        Self {
+            mode: ScheduleMode::Interval,
+            cron: String::new(),
+            samples_per_window: 4,
+            min_gap_minutes: 20,
+            enabled: false,
+            monday: workday.clone(),
+            tuesday: workday.clone(),
+            wednesday: workday.clone(),
+            thursday: workday.clone(),
+            friday: workday,
+            saturday: Vec::new(),
+            sunday: Vec::new(),
+            lunch: Some(TimeWindow::new(
+                NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(13, 0, 0).unwrap(),
+            )),
+            skip_dates: Vec::new(),
+        }
+    }
+}
+
+impl ScheduleConfig {
+    pub fn parse_cron(&self) -> Result<Cron> {
+        Cron::new(self.cron.trim())
+            .parse()
+            .with_context(|| format!("Expressão cron inválida: \"{}\"", self.cron))
+    }
+
+    pub fn windows(&self, weekday: Weekday) -> &[TimeWindow] {
+        match weekday {
+            Weekday::Mon => &self.monday,
+            Weekday::Tue => &self.tuesday,
+            Weekday::Wed => &self.wednesday,
+            Weekday::Thu => &self.thursday,
+            Weekday::Fri => &self.friday,
+            Weekday::Sat => &self.saturday,
+            Weekday::Sun => &self.sunday,
+        }
+    }
+
+    pub fn windows_mut(&mut self, weekday: Weekday) -> &mut Vec<TimeWindow> {
+        match weekday {
+            Weekday::Mon => &mut self.monday,
+            Weekday::Tue => &mut self.tuesday,
+            Weekday::Wed => &mut self.wednesday,
+            Weekday::Thu => &mut self.thursday,
+            Weekday::Fri => &mut self.friday,
+            Weekday::Sat => &mut self.saturday,
+            Weekday::Sun => &mut self.sunday,
         }
     }
 }
 
+// Como os horários dos inquéritos são calculados
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScheduleMode {
+    // A cada `daemon_interval_minutes` a partir do último inquérito
+    #[default]
+    Interval,
+    // Em múltiplos do intervalo contados da meia-noite (ex.: 30 min → :00 e :30)
+    Aligned,
+    // Nos horários de uma expressão cron
+    Cron,
+    // Em horários sorteados dentro de cada janela ativa (amostragem de experiências)
+    Sampling,
+}
+
+impl ScheduleMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScheduleMode::Interval => "Intervalo desde o último inquérito",
+            ScheduleMode::Aligned => "Alinhado ao relógio",
+            ScheduleMode::Cron => "Expressão cron",
+            ScheduleMode::Sampling => "Amostragem aleatória",
+        }
+    }
+
+    // Nome gravado no config.toml e junto de cada resposta
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduleMode::Interval => "interval",
+            ScheduleMode::Aligned => "aligned",
+            ScheduleMode::Cron => "cron",
+            ScheduleMode::Sampling => "sampling",
+        }
+    }
+}
+
+// Comandos executados (pelo shell) quando algo acontece. Comando vazio
+// desativa o evento. Ver `hooks.rs` para o que cada comando recebe.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    pub on_activity_saved: String,
+    pub on_activity_continued: String,
+    pub on_inquiry_skipped: String,
+    pub on_daemon_start: String,
+    // Tempo máximo de cada comando; depois disso ele é encerrado
+    pub timeout_seconds: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            on_activity_saved: String::new(),
+            on_activity_continued: String::new(),
+            on_inquiry_skipped: String::new(),
+            on_daemon_start: String::new(),
+            timeout_seconds: 10,
+        }
+    }
+}
+
+// Envio das atividades registradas e continuadas, via POST, para serviços HTTP.
+// As entregas passam pela tabela `webhook_outbox`, então o que foi registrado
+// sem conexão é enviado depois.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    pub urls: Vec<String>,
+    pub timeout_seconds: u64,
+    // Tentativas de cada entrega antes de desistir (`acv-inq webhooks flush` tenta de novo)
+    pub max_attempts: u32,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            urls: Vec::new(),
+            timeout_seconds: 10,
+            max_attempts: 10,
+        }
+    }
+}
+
+// API HTTP local aberta por `acv-inq serve`, sempre em 127.0.0.1. Com `token`
+// preenchido, as requisições precisam do cabeçalho `Authorization: Bearer <token>`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: 7464,
+            token: String::new(),
+        }
+    }
+}
+
+// Como o inquérito pergunta: na janela do acv-inq, num terminal ou por um
+// seletor externo (rofi, dmenu, zenity, fzf...)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrompterConfig {
+    pub kind: PrompterKind,
+    // Modo `terminal`: onde perguntar (ex.: /dev/pts/3); vazio usa o terminal do processo
+    pub tty: String,
+    // Modo `command`: executado pelo shell com a pergunta em ACV_QUESTION e as
+    // opções na entrada padrão; a primeira linha da saída é a resposta
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PrompterKind {
+    #[default]
+    Gui,
+    Terminal,
+    Command,
+}
+
+impl PrompterKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PrompterKind::Gui => "Janela",
+            PrompterKind::Terminal => "Terminal",
+            PrompterKind::Command => "Comando externo",
+        }
+    }
+
+    // Nome gravado no config.toml e passado em `inquiry --prompter`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrompterKind::Gui => "gui",
+            PrompterKind::Terminal => "terminal",
+            PrompterKind::Command => "command",
+        }
+    }
+}
+
+// O que registrar quando um inquérito expira sem resposta
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UnansweredPolicy {
+    // Não registra nada; o período fica sem atividade
+    #[default]
+    Nothing,
+    // Registra uma entrada "Ausente" a partir do horário do inquérito
+    Away,
+    // Repete a última atividade do dia, marcada como automática
+    RepeatLast,
+}
+
+impl UnansweredPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            UnansweredPolicy::Nothing => "Não registrar nada",
+            UnansweredPolicy::Away => "Registrar como ausente",
+            UnansweredPolicy::RepeatLast => "Repetir a última atividade",
+        }
+    }
+}
+
+// Intervalo de horário dentro de um mesmo dia, gravado como "HH:MM-HH:MM"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    pub fn parse(value: &str) -> Result<Self> {
+        let (start, end) = value
+            .split_once('-')
+            .with_context(|| format!("Horário inválido: \"{value}\". Use HH:MM-HH:MM"))?;
+        let parse_time = |time: &str| {
+            NaiveTime::parse_from_str(time.trim(), "%H:%M")
+                .with_context(|| format!("Horário inválido: \"{value}\". Use HH:MM-HH:MM"))
+        };
+        let (start, end) = (parse_time(start)?, parse_time(end)?);
+
+        if end <= start {
+            bail!("Horário inválido: \"{value}\". O fim deve ser depois do início");
+        }
+
+        Ok(Self { start, end })
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+impl TryFrom<String> for TimeWindow {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        Self::parse(&value).map_err(|e| e.to_string())
+    }
+}
+
+impl From<TimeWindow> for String {
+    fn from(window: TimeWindow) -> Self {
+        window.to_string()
+    }
+}
+
 impl AppConfig {
     pub fn load() -> Result<Self> {
-        let config_path = Self::get_config_path()?;
+        Self::load_from(&Self::get_config_path()?)
+    }
 
+    pub fn load_from(config_path: &Path) -> Result<Self> {
         if config_path.exists() {
-            let content = fs::read_to_string(&config_path).with_context(|| {
+            let content = fs::read_to_string(config_path).with_context(|| {
                 format!("Falha ao ler arquivo de configuração: {config_path:?}")
             })?;
 
             let config: AppConfig = toml::from_str(&content)
                 .with_context(|| "Falha ao parsear arquivo de configuração")?;
+            config.validate()?;
 
             Ok(config)
         } else {
             // Criar configuração padrão se não existir
             let config = Self::default();
-            config.save()?;
+            config.save_to(config_path)?;
             Ok(config)
         }
     }
 
+    pub fn validate(&self) -> Result<()> {
+        if self.daemon_interval_minutes == 0 {
+            bail!("daemon_interval_minutes deve ser maior que zero");
+        }
+        if self.inquiry_timeout_minutes == 0 {
+            bail!("inquiry_timeout_minutes deve ser maior que zero");
+        }
+        if self.hooks.timeout_seconds == 0 {
+            bail!("hooks.timeout_seconds deve ser maior que zero");
+        }
+        if let Some(url) = self
+            .webhooks
+            .urls
+            .iter()
+            .find(|url| !url.starts_with("http://") && !url.starts_with("https://"))
+        {
+            bail!("URL de webhook inválida: \"{url}\". Use http:// ou https://");
+        }
+        if self.webhooks.timeout_seconds == 0 || self.webhooks.max_attempts == 0 {
+            bail!("webhooks.timeout_seconds e webhooks.max_attempts devem ser maiores que zero");
+        }
+        if self.prompter.kind == PrompterKind::Command && self.prompter.command.trim().is_empty() {
+            bail!("prompter.command deve ser preenchido quando prompter.kind = \"command\"");
+        }
+        if self.server.port == 0 {
+            bail!("server.port deve ser maior que zero");
+        }
+        if self.schedule.mode == ScheduleMode::Cron {
+            self.schedule.parse_cron()?;
+        }
+        if self.schedule.mode == ScheduleMode::Sampling {
+            if self.schedule.samples_per_window == 0 {
+                bail!("samples_per_window deve ser maior que zero");
+            }
+            // Sem janelas, o sorteio cairia em qualquer hora do dia, inclusive de madrugada
+            if !self.schedule.enabled {
+                bail!(
+                    "O modo sampling sorteia horários no horário de trabalho; ative schedule.enabled"
+                );
+            }
+        }
+        Ok(())
+    }
+
     pub fn save(&self) -> Result<()> {
-        let config_path = Self::get_config_path()?;
+        self.save_to(&Self::get_config_path()?)
+    }
 
+    pub fn save_to(&self, config_path: &Path) -> Result<()> {
         // Criar diretório se não existir
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)
@@ -53,13 +432,17 @@ impl AppConfig {
         let content =
             toml::to_string_pretty(self).with_context(|| "Falha ao serializar configuração")?;
 
-        fs::write(&config_path, content)
+        fs::write(config_path, content)
             .with_context(|| format!("Falha ao salvar arquivo de configuração: {config_path:?}"))?;
 
         Ok(())
     }
 
-    fn get_config_path() -> Result<PathBuf> {
+    pub fn get_config_path() -> Result<PathBuf> {
+        if let Some(path) = &options::get().config {
+            return Ok(path.clone());
+        }
+
         let config_dir = if cfg!(target_os = "windows") {
             dirs::config_dir()
                 .context("Não foi possível encontrar o diretório de configuração no Windows")?
@@ -74,10 +457,50 @@ impl AppConfig {
         Ok(config_dir.join("activity-inquirer").join("config.toml"))
     }
 
+    // Data de modificação do arquivo de configuração, usada pelo daemon para
+    // perceber alterações feitas pela interface ou por um editor de texto
+    pub fn modified_at() -> Option<SystemTime> {
+        let config_path = Self::get_config_path().ok()?;
+        fs::metadata(config_path).ok()?.modified().ok()
+    }
+
+    // Lista legível das opções que mudaram em relação a `previous`,
+    // no formato "chave: antigo → novo"
+    pub fn changes_from(&self, previous: &AppConfig) -> Vec<String> {
+        let mut old_values = BTreeMap::new();
+        let mut new_values = BTreeMap::new();
+        if let Ok(value) = toml::Value::try_from(previous) {
+            flatten_toml("", &value, &mut old_values);
+        }
+        if let Ok(value) = toml::Value::try_from(self) {
+            flatten_toml("", &value, &mut new_values);
+        }
+
+        let keys: BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let old_value = old_values.get(key).map(String::as_str).unwrap_or("-");
+                let new_value = new_values.get(key).map(String::as_str).unwrap_or("-");
+                if old_value == new_value {
+                    None
+                } else if key == "server.token" {
+                    // O token não vai para o log
+                    Some(format!("{key}: alterado"))
+                } else {
+                    Some(format!("{key}: {old_value} → {new_value}"))
+                }
+            })
+            .collect()
+    }
+
     pub fn get_daemon_interval_seconds(&self) -> u64 {
         self.daemon_interval_minutes * 60
     }
 
+    pub fn get_inquiry_timeout_seconds(&self) -> u64 {
+        self.inquiry_timeout_minutes * 60
+    }
+
     pub fn get_available_intervals() -> Vec<(String, u64)> {
         vec![
             ("1 minuto".to_string(), 1),
@@ -96,6 +519,21 @@ impl AppConfig {
         Self::format_interval_static(self.daemon_interval_minutes)
     }
 
+    pub fn describe_schedule(&self) -> String {
+        match self.schedule.mode {
+            ScheduleMode::Interval => format!("a cada {}", self.format_interval()),
+            ScheduleMode::Aligned => {
+                format!("a cada {}, alinhado ao relógio", self.format_interval())
+            }
+            ScheduleMode::Cron => format!("cron \"{}\"", self.schedule.cron.trim()),
+            ScheduleMode::Sampling => format!(
+                "{} horários sorteados por janela, com pelo menos {} entre eles",
+                self.schedule.samples_per_window,
+                Self::format_interval_static(self.schedule.min_gap_minutes)
+            ),
+        }
+    }
+
     pub fn format_interval_static(minutes: u64) -> String {
         if minutes < 60 {
             format!("{} minuto{}", minutes, if minutes == 1 { "" } else { "s" })
@@ -110,3 +548,80 @@ impl AppConfig {
         }
     }
 }
+
+fn flatten_toml(prefix: &str, value: &toml::Value, out: &mut BTreeMap<String, String>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_toml(&key, value, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    #[test]
+    fn load_from_missing_file_writes_defaults() {
+        let temp = TempDir::new("config-defaults");
+        let path = temp.join("outro/config.toml");
+
+        let config = AppConfig::load_from(&path).unwrap();
+
+        assert!(path.exists());
+        assert_eq!(
+            config.daemon_interval_minutes,
+            AppConfig::default().daemon_interval_minutes
+        );
+    }
+
+    #[test]
+    fn save_to_and_load_from_round_trip() {
+        let temp = TempDir::new("config-round-trip");
+        let path = temp.join("config.toml");
+        let config = AppConfig {
+            daemon_interval_minutes: 45,
+            auto_start_daemon: true,
+            ..AppConfig::default()
+        };
+
+        config.save_to(&path).unwrap();
+        let loaded = AppConfig::load_from(&path).unwrap();
+
+        assert_eq!(loaded.daemon_interval_minutes, 45);
+        assert!(loaded.auto_start_daemon);
+    }
+
+    #[test]
+    fn load_from_rejects_invalid_file() {
+        let temp = TempDir::new("config-invalid");
+        let path = temp.join("config.toml");
+        fs::write(&path, "daemon_interval_minutes = 0\n").unwrap();
+
+        assert!(AppConfig::load_from(&path).is_err());
+    }
+
+    #[test]
+    fn sampling_requires_working_hours() {
+        let mut config = AppConfig::default();
+        config.schedule.mode = ScheduleMode::Sampling;
+        assert!(config.validate().is_err());
+
+        config.schedule.enabled = true;
+        assert!(config.validate().is_ok());
+
+        config.schedule.samples_per_window = 0;
+        assert!(config.validate().is_err());
+    }
+}
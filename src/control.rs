@@ -0,0 +1,293 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::config::AppConfig;
+use crate::database::Database;
+use crate::instance;
+use crate::models::format_duration;
+use crate::scheduler;
+
+// Comandos aceitos pelo socket de controle do daemon. O protocolo é uma linha
+// JSON por requisição (ex.: {"command":"status"}) e uma linha JSON de resposta.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, clap::Subcommand)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum ControlRequest {
+    /// Mostrar o estado do daemon em execução
+    Status,
+    /// Pausar os inquéritos agendados
+    Pause,
+    /// Retomar os inquéritos agendados
+    Resume,
+    /// Abrir um inquérito agora
+    AskNow,
+    /// Encerrar o daemon
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub started_at: DateTime<Local>,
+    pub inquiry_count: u32,
+    #[serde(default)]
+    pub skipped_count: u32,
+    pub paused: bool,
+    pub inquiry_running: bool,
+    pub schedule: String,
+    pub next_inquiry: Option<DateTime<Local>>,
+    pub last_outcome: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResponse {
+    pub ok: bool,
+    pub message: String,
+    #[serde(default)]
+    pub status: Option<DaemonStatus>,
+}
+
+impl ControlResponse {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self {
+            ok: true,
+            message: message.into(),
+            status: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            message: message.into(),
+            status: None,
+        }
+    }
+}
+
+// Requisição recebida pelo socket, com o canal para devolver a resposta
+pub type ControlMessage = (ControlRequest, oneshot::Sender<ControlResponse>);
+
+// Socket de controle aberto pelo daemon. O arquivo é removido quando o servidor
+// é descartado.
+pub struct ControlServer {
+    path: PathBuf,
+    pub receiver: mpsc::Receiver<ControlMessage>,
+    accept_task: JoinHandle<()>,
+    // Cada conexão guarda uma cópia do emissor; o canal fecha quando todas terminam
+    connections_done: mpsc::Receiver<()>,
+}
+
+impl ControlServer {
+    // Para de aceitar conexões e espera as respostas pendentes serem enviadas,
+    // para que o cliente de `stop` receba a confirmação antes do processo sair
+    pub async fn shutdown(mut self) {
+        self.accept_task.abort();
+        let _ = (&mut self.accept_task).await;
+        let _ = tokio::time::timeout(Duration::from_secs(2), self.connections_done.recv()).await;
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+pub fn socket_path() -> Result<PathBuf> {
+    instance::runtime_file("activity-inquirer.sock")
+}
+
+#[cfg(unix)]
+pub fn listen() -> Result<Option<ControlServer>> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{UnixListener, UnixStream};
+
+    let path = socket_path()?;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(&path).is_ok() {
+            bail!("Já existe um daemon em execução (socket em {path:?})");
+        }
+        // Socket de uma execução anterior que não foi encerrada corretamente
+        std::fs::remove_file(&path)
+            .with_context(|| format!("Falha ao remover socket antigo: {path:?}"))?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Falha ao criar diretório do socket: {parent:?}"))?;
+    }
+
+    let listener = UnixListener::bind(&path)
+        .with_context(|| format!("Falha ao abrir socket de controle em {path:?}"))?;
+    let (sender, receiver) = mpsc::channel(8);
+    let (connection_guard, connections_done) = mpsc::channel(1);
+
+    async fn handle_connection(
+        stream: UnixStream,
+        sender: mpsc::Sender<ControlMessage>,
+        _guard: mpsc::Sender<()>,
+    ) {
+        let (reader, mut writer) = stream.into_split();
+        let mut line = String::new();
+        if BufReader::new(reader).read_line(&mut line).await.is_err() {
+            return;
+        }
+
+        let response = match serde_json::from_str::<ControlRequest>(line.trim()) {
+            Ok(request) => {
+                let (reply, response) = oneshot::channel();
+                if sender.send((request, reply)).await.is_err() {
+                    return;
+                }
+                match response.await {
+                    Ok(response) => response,
+                    Err(_) => return,
+                }
+            }
+            Err(e) => ControlResponse::error(format!("Requisição inválida: {e}")),
+        };
+
+        if let Ok(mut json) = serde_json::to_string(&response) {
+            json.push('\n');
+            let _ = writer.write_all(json.as_bytes()).await;
+        }
+    }
+
+    let accept_task = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(handle_connection(
+                        stream,
+                        sender.clone(),
+                        connection_guard.clone(),
+                    ));
+                }
+                Err(e) => log::warn!("⚠️  Erro no socket de controle: {e}"),
+            }
+        }
+    });
+
+    Ok(Some(ControlServer {
+        path,
+        receiver,
+        accept_task,
+        connections_done,
+    }))
+}
+
+#[cfg(not(unix))]
+pub fn listen() -> Result<Option<ControlServer>> {
+    Ok(None)
+}
+
+#[cfg(unix)]
+fn send(request: ControlRequest) -> Result<ControlResponse> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let path = socket_path()?;
+    let mut stream = UnixStream::connect(&path)
+        .with_context(|| format!("O daemon não está em execução (socket {path:?} indisponível)"))?;
+    stream
+        .set_read_timeout(Some(Duration::from_secs(10)))
+        .context("Falha ao configurar socket de controle")?;
+
+    let mut json = serde_json::to_string(&request).context("Falha ao serializar comando")?;
+    json.push('\n');
+    stream
+        .write_all(json.as_bytes())
+        .context("Falha ao enviar comando ao daemon")?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .context("Falha ao ler resposta do daemon")?;
+
+    serde_json::from_str(line.trim()).context("Resposta inválida do daemon")
+}
+
+#[cfg(not(unix))]
+fn send(_request: ControlRequest) -> Result<ControlResponse> {
+    bail!("O controle do daemon só está disponível em sistemas Unix")
+}
+
+// Envia um comando ao daemon em execução e imprime a resposta
+pub fn run_action(request: ControlRequest) -> Result<()> {
+    let response = send(request)?;
+    if !response.ok {
+        bail!("{}", response.message);
+    }
+
+    match response.status {
+        Some(status) => print_status(&status),
+        None => println!("✅ {}", response.message),
+    }
+
+    Ok(())
+}
+
+fn print_status(status: &DaemonStatus) {
+    let state = if status.inquiry_running {
+        "inquérito aberto"
+    } else if status.paused {
+        "pausado"
+    } else {
+        "aguardando"
+    };
+
+    println!("🤖 Daemon em execução (PID {})", status.pid);
+    println!(
+        "  • Ativo há: {} (desde {})",
+        format_duration(Local::now() - status.started_at),
+        status.started_at.format("%Y-%m-%d %H:%M")
+    );
+    println!("  • Estado: {state}");
+    println!("  • Agenda: {}", status.schedule);
+    println!("  • Inquéritos realizados: {}", status.inquiry_count);
+    if status.skipped_count > 0 {
+        println!(
+            "  • Inquéritos ignorados (janela já aberta): {}",
+            status.skipped_count
+        );
+    }
+    match status.next_inquiry {
+        Some(next) => println!("  • Próximo inquérito: {}", scheduler::format_next(next)),
+        None if status.paused => println!("  • Próximo inquérito: — (pausado)"),
+        None => println!("  • Próximo inquérito: — (nenhum horário ativo na agenda)"),
+    }
+    println!(
+        "  • Último resultado: {}",
+        status
+            .last_outcome
+            .as_deref()
+            .unwrap_or("nenhum inquérito ainda")
+    );
+
+    let week_ago = Local::now() - chrono::Duration::days(7);
+    if let Ok((total, answered)) = Database::new().and_then(|db| db.response_rate(week_ago))
+        && total > 0
+    {
+        println!(
+            "  • Taxa de resposta (7 dias): {}% ({answered} de {total})",
+            answered * 100 / total
+        );
+    }
+
+    if let Ok(config) = AppConfig::load()
+        && !config.webhooks.urls.is_empty()
+        && let Ok(queue) =
+            Database::new().and_then(|db| db.webhook_queue(config.webhooks.max_attempts))
+        && queue.pending + queue.abandoned > 0
+    {
+        println!(
+            "  • Webhooks não entregues: {} (veja `acv-inq webhooks status`)",
+            queue.pending + queue.abandoned
+        );
+    }
+}
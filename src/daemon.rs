@@ -1,121 +1,1083 @@
-use anyhow::Result;
-use chrono::Local;
-use std::time::Duration;
-use tokio::time;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, SystemTime};
+use tokio::io::AsyncReadExt;
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Instant};
 
-use crate::config::AppConfig;
+use crate::config::{AppConfig, PrompterKind, UnansweredPolicy};
+use crate::control::{self, ControlMessage, ControlRequest, ControlResponse, ControlServer};
+use crate::database::Database;
+use crate::hooks::{HookEvent, Hooks};
+use crate::instance;
+use crate::models::{
+    Activity, ActivityInput, ActivityKind, InquiryOutcome, InquiryReport, PromptContext,
+    format_duration,
+};
+use crate::options;
+use crate::scheduler::{Schedule, format_next};
+use crate::webhooks::Webhooks;
 
-// Função para executar inquérito usando processo separado
-async fn run_inquiry_safe() -> Result<()> {
-    use tokio::process::Command;
-    use std::env;
+// Intervalo entre verificações do arquivo de configuração
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
 
-    // Obter o caminho do executável atual
-    let current_exe = env::current_exe()
-        .map_err(|e| anyhow::anyhow!("Não foi possível obter caminho do executável: {e}"))?;
+// Intervalo entre novas tentativas de entregar os webhooks pendentes
+const WEBHOOK_RETRY_INTERVAL: Duration = Duration::from_secs(60);
 
-    println!("🚀 Iniciando inquérito em processo separado...");
+// Menor avanço do relógio além do tempo monotônico, entre duas voltas do laço,
+// que conta como suspensão (ajustes do NTP ficam bem abaixo disso)
+const CLOCK_JUMP_SLACK: Duration = Duration::from_secs(60);
 
-    // Executar o inquérito em um processo separado com timeout
-    let mut child = Command::new(&current_exe)
-        .arg("--inquiry")
-        .spawn()
-        .map_err(|e| anyhow::anyhow!("Erro ao iniciar processo de inquérito: {e}"))?;
+// Ao receber SIGINT/SIGTERM, tempo que o inquérito aberto tem para terminar
+// sozinho (com Ctrl+C no terminal, ele recebe o mesmo sinal) antes de ser encerrado
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
 
-    // Aguardar com timeout de 5 minutos
-    let timeout_duration = Duration::from_secs(300);
+// Estado salvo entre execuções do daemon, para que um reinício não repita um
+// inquérito recente nem zere os contadores. O próximo horário não é guardado: é
+// recalculado a partir do último inquérito e da agenda, que pode ter mudado.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct DaemonState {
+    last_inquiry: Option<DateTime<Local>>,
+    inquiry_count: u32,
+    skipped_count: u32,
+    saved_at: Option<DateTime<Local>>,
+}
 
-    match tokio::time::timeout(timeout_duration, child.wait()).await {
-        Ok(Ok(status)) => {
-            if status.success() {
-                println!("✅ Inquérito concluído com sucesso!");
-                Ok(())
-            } else {
-                eprintln!("⚠️  Inquérito terminou com código: {}", status.code().unwrap_or(-1));
-                Ok(()) // Não falhar o daemon
-            }
-        }
-        Ok(Err(e)) => {
-            eprintln!("⚠️  Erro ao aguardar processo de inquérito: {e}");
-            Ok(()) // Não falhar o daemon
-        }
-        Err(_) => {
-            eprintln!("⚠️  Timeout no inquérito (5 minutos). Terminando processo...");
-            let _ = child.kill().await;
-            Ok(()) // Não falhar o daemon por timeout
+impl DaemonState {
+    // Fica ao lado do banco de dados, já que descreve os inquéritos registrados nele
+    fn path() -> Result<PathBuf> {
+        Ok(Database::get_db_path()?.with_extension("daemon-state.json"))
+    }
+
+    fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
         }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Falha ao ler estado do daemon: {path:?}"))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Falha ao interpretar estado do daemon: {path:?}"))
+    }
+
+    // Grava num arquivo temporário e renomeia, para nunca deixar um estado pela metade
+    fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        let temp = path.with_extension("json.tmp");
+        let content =
+            serde_json::to_string_pretty(self).context("Falha ao serializar estado do daemon")?;
+
+        fs::write(&temp, content)
+            .and_then(|_| fs::rename(&temp, &path))
+            .with_context(|| format!("Falha ao salvar estado do daemon: {path:?}"))
     }
 }
 
-pub async fn run_daemon() -> Result<()> {
-    // Carregar configuração
-    let config = AppConfig::load()?;
-    println!("🤖 Iniciando modo daemon - Activity Inquirer");
-    println!("⏰ Intervalo configurado: {}", config.format_interval());
-    println!("💡 Pressione Ctrl+C para parar o daemon");
-    println!();
+// Inquérito em execução num processo separado
+struct RunningInquiry {
+    child: Child,
+    number: u32,
+    context: PromptContext,
+    started_at: DateTime<Local>,
+    deadline: Instant,
+}
+
+// Eventos que o laço principal do daemon trata, um de cada vez
+enum Event {
+    Due,
+    InquiryExited(std::io::Result<ExitStatus>),
+    InquiryTimedOut,
+    Control(ControlMessage),
+    ConfigPoll,
+    WebhookRetry,
+    Shutdown(&'static str),
+}
 
-    // Executar inquérito imediatamente ao iniciar
-    println!(
-        "📝 Executando primeiro inquérito... ({})",
-        Local::now().format("%H:%M:%S")
-    );
-    if let Err(e) = run_inquiry_safe().await {
-        eprintln!("❌ Erro no inquérito inicial: {e}");
-    } else {
-        println!("✅ Primeiro inquérito concluído!");
+struct Daemon {
+    config: AppConfig,
+    config_modified: Option<SystemTime>,
+    schedule: Schedule,
+    started_at: DateTime<Local>,
+    last_inquiry: Option<DateTime<Local>>,
+    next_due: Option<DateTime<Local>>,
+    // Última vez que o laço principal rodou, no relógio e no tempo monotônico
+    // (que não corre durante a suspensão)
+    heartbeat: DateTime<Local>,
+    heartbeat_monotonic: Instant,
+    // Início de um período sem inquéritos (suspensão) ainda não perguntado
+    pending_backfill: Option<DateTime<Local>>,
+    inquiry_count: u32,
+    // Horários pulados porque já havia uma janela de inquérito aberta
+    skipped_count: u32,
+    paused: bool,
+    inquiry: Option<RunningInquiry>,
+    last_outcome: Option<String>,
+    webhook_delivery: Option<JoinHandle<()>>,
+}
+
+impl Daemon {
+    fn new(config: AppConfig, state: DaemonState) -> Result<Self> {
+        let now = Local::now();
+        let schedule = Schedule::from_config(&config)?;
+        // Um horário no futuro indica relógio alterado; nesse caso o estado é ignorado
+        let last_inquiry = state.last_inquiry.filter(|last| *last <= now);
+        Ok(Self {
+            next_due: schedule.next_inquiry(last_inquiry, now),
+            config,
+            config_modified: AppConfig::modified_at(),
+            schedule,
+            started_at: now,
+            last_inquiry,
+            heartbeat: now,
+            heartbeat_monotonic: Instant::now(),
+            pending_backfill: None,
+            inquiry_count: state.inquiry_count,
+            skipped_count: state.skipped_count,
+            paused: false,
+            inquiry: None,
+            last_outcome: None,
+            webhook_delivery: None,
+        })
     }
-    println!();
 
-    // Configurar timer para executar no intervalo especificado
-    let interval_seconds = config.get_daemon_interval_seconds();
-    let mut interval = time::interval(Duration::from_secs(interval_seconds));
+    async fn run(&mut self, mut control: Option<ControlServer>) -> Result<()> {
+        let mut signals = ShutdownSignals::new()?;
+        let mut config_poll = time::interval(CONFIG_POLL_INTERVAL);
+        config_poll.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+        let mut webhook_retry = time::interval(WEBHOOK_RETRY_INTERVAL);
+        webhook_retry.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
 
-    // Pular o primeiro tick (já executamos o inquérito inicial)
-    interval.tick().await;
+        loop {
+            let waiting = !self.paused && self.inquiry.is_none() && self.next_due.is_some();
+            let next_due = self.next_due.unwrap_or_else(Local::now);
+            let deadline = self.inquiry.as_ref().map(|inquiry| inquiry.deadline);
 
-    let mut inquiry_count = 1;
+            // A espera é recalculada a cada evento (e a cada verificação da
+            // configuração), então acompanha o relógio mesmo após suspensões
+            let event = tokio::select! {
+                _ = time::sleep(duration_until(next_due)), if waiting => Event::Due,
+                status = wait_inquiry(&mut self.inquiry) => Event::InquiryExited(status),
+                _ = time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    Event::InquiryTimedOut
+                }
+                Some(message) = recv_control(&mut control) => Event::Control(message),
+                _ = config_poll.tick() => Event::ConfigPoll,
+                _ = webhook_retry.tick() => Event::WebhookRetry,
+                signal = signals.recv() => Event::Shutdown(signal),
+            };
 
-    loop {
-        // Aguardar próximo tick
-        interval.tick().await;
+            self.check_clock_jump();
 
-        inquiry_count += 1;
-        let now = Local::now();
+            match event {
+                Event::Due => match self.pending_backfill.take() {
+                    Some(since) => self.start_inquiry(
+                        Some(Local::now()),
+                        &PromptContext::backfill(),
+                        Some(since),
+                    ),
+                    None => {
+                        let context = PromptContext {
+                            schedule_mode: self.config.schedule.mode.as_str().to_string(),
+                            planned_at: Some(next_due),
+                        };
+                        self.start_inquiry(Some(next_due), &context, None);
+                    }
+                },
+                Event::InquiryExited(status) => self.finish_inquiry(status).await,
+                Event::InquiryTimedOut => self.kill_inquiry(InquiryOutcome::TimedOut).await,
+                Event::Control((request, reply)) => {
+                    debug!("Comando de controle recebido: {request:?}");
+                    let stop = request == ControlRequest::Stop;
+                    let response = self.handle_control(request);
+                    if stop {
+                        self.kill_inquiry(InquiryOutcome::Interrupted).await;
+                        self.save_state();
+                        let _ = reply.send(response);
+                        if let Some(server) = control.take() {
+                            server.shutdown().await;
+                        }
+                        info!("🛑 Daemon encerrado pelo comando stop");
+                        return Ok(());
+                    }
+                    let _ = reply.send(response);
+                }
+                Event::ConfigPoll => self.reload_config_if_changed(),
+                Event::WebhookRetry => self.deliver_webhooks(),
+                Event::Shutdown(signal) => {
+                    info!("🛑 {signal} recebido; encerrando o daemon");
+                    self.end_inquiry(SHUTDOWN_GRACE).await;
+                    self.save_state();
+                    if let Some(server) = control.take() {
+                        server.shutdown().await;
+                    }
+                    info!("👋 Daemon encerrado");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    // Percebe quando o relógio avançou, além do tempo monotônico (que não corre
+    // durante a suspensão ou hibernação), mais que o intervalo entre inquéritos
+    // desde a última volta do laço, e agenda um inquérito de preenchimento do
+    // período perdido para o próximo horário ativo. O laço acorda pelo menos a
+    // cada CONFIG_POLL_INTERVAL, então a diferença é o tempo suspenso.
+    fn check_clock_jump(&mut self) {
+        self.check_clock_jump_at(Local::now(), Instant::now());
+    }
 
-        println!(
+    fn check_clock_jump_at(&mut self, now: DateTime<Local>, monotonic: Instant) {
+        let gap = now - self.heartbeat;
+        let elapsed = monotonic - self.heartbeat_monotonic;
+        let since = self.heartbeat;
+        self.heartbeat = now;
+        self.heartbeat_monotonic = monotonic;
+
+        // Só um salto maior que o intervalo entre inquéritos perde alguma pergunta
+        let threshold =
+            Duration::from_secs(self.config.get_daemon_interval_seconds()).max(CLOCK_JUMP_SLACK);
+        let suspended = gap
+            .to_std()
+            .is_ok_and(|gap| gap.saturating_sub(elapsed) > threshold);
+        if !suspended {
+            return;
+        }
+
+        info!(
+            "💤 O relógio avançou {} desde a última verificação ({} → {}); provável suspensão",
+            format_duration(gap),
+            since.format("%H:%M"),
+            now.format("%H:%M")
+        );
+
+        self.pending_backfill = Some(
+            self.pending_backfill
+                .map_or(since, |pending| pending.min(since)),
+        );
+        if !self.paused && self.inquiry.is_none() {
+            self.next_due = self.schedule.next_active(now);
+            self.print_next_inquiry();
+        }
+    }
+
+    // Com `scheduled_at`, o inquérito ocupa esse horário da agenda e o próximo
+    // conta a partir dele. Sem ele (`ask-now`), a agenda não muda.
+    fn start_inquiry(
+        &mut self,
+        scheduled_at: Option<DateTime<Local>>,
+        context: &PromptContext,
+        backfill_since: Option<DateTime<Local>>,
+    ) {
+        if let Some(scheduled_at) = scheduled_at {
+            self.last_inquiry = Some(scheduled_at);
+            self.reschedule();
+        }
+
+        // Uma janela aberta fora do daemon (ex.: `acv-inq inquiry`) ocupa o
+        // lugar deste inquérito: ela vem para frente e o horário é pulado
+        match instance::open_inquiry() {
+            Ok(Some(holder)) => {
+                self.skip_inquiry(holder, context);
+                return;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("⚠️  Falha ao verificar janela de inquérito aberta: {e}"),
+        }
+        let started_at = Local::now();
+
+        self.inquiry_count += 1;
+        let number = self.inquiry_count;
+
+        info!(
             "⏰ Hora do inquérito #{} ({})",
-            inquiry_count,
-            now.format("%H:%M:%S")
+            number,
+            Local::now().format("%H:%M:%S")
         );
+        if let Some(since) = backfill_since {
+            info!(
+                "📝 Perguntando sobre o período sem respostas desde {}",
+                since.format("%d/%m %H:%M")
+            );
+        }
 
-        // Executar inquérito
-        match run_inquiry_safe().await {
-            Ok(_) => {
-                println!("✅ Inquérito #{inquiry_count} concluído com sucesso!");
+        match spawn_inquiry(context, backfill_since, self.config.prompter.kind) {
+            Ok(child) => {
+                debug!(
+                    "Inquérito #{number} em execução (PID {})",
+                    child.id().unwrap_or_default()
+                );
+                self.inquiry = Some(RunningInquiry {
+                    child,
+                    number,
+                    context: context.clone(),
+                    started_at,
+                    deadline: Instant::now()
+                        + Duration::from_secs(self.config.get_inquiry_timeout_seconds()),
+                });
             }
             Err(e) => {
-                eprintln!("❌ Erro no inquérito #{inquiry_count}: {e}");
-                eprintln!("🔄 Continuando execução...");
+                error!("❌ Erro no inquérito #{number}: {e}");
+                info!("🔄 Continuando execução...");
+                self.record_outcome(number, format!("falhou ao iniciar ({e})"));
+                store_outcome(
+                    &InquiryReport::new(InquiryOutcome::Failed),
+                    context,
+                    started_at,
+                    None,
+                );
             }
         }
+        self.save_state();
+    }
 
-        // Calcular próximo inquérito
-        let next_inquiry = now + chrono::Duration::minutes(config.daemon_interval_minutes as i64);
-        println!(
-            "📅 Próximo inquérito em: {}",
-            next_inquiry.format("%H:%M:%S")
+    fn skip_inquiry(&mut self, holder: instance::Holder, context: &PromptContext) {
+        self.skipped_count += 1;
+        info!(
+            "⏭️  Inquérito ignorado ({}): já existe uma janela de inquérito aberta ({holder})",
+            Local::now().format("%H:%M:%S")
         );
-        println!();
+        if let Err(e) = instance::request_inquiry_focus() {
+            warn!("⚠️  {e}");
+        }
+        self.last_outcome = Some(format!(
+            "ignorado: janela de inquérito já aberta ({holder}) às {}",
+            Local::now().format("%H:%M:%S")
+        ));
+        store_outcome(
+            &InquiryReport::new(InquiryOutcome::Skipped),
+            context,
+            Local::now(),
+            None,
+        );
+        self.notify(HookEvent::InquirySkipped, None);
+        self.save_state();
+    }
+
+    // Hooks e webhooks rodam fora do laço principal, para não atrasar a agenda
+    // nem os comandos de controle
+    fn notify(&self, event: HookEvent, activity: Option<Activity>) {
+        let hooks = Hooks::new(self.config.hooks.clone());
+        let webhooks = Webhooks::new(self.config.webhooks.clone());
+        let send = activity.is_some() && webhooks.is_enabled();
+        if !hooks.is_set(event) && !send {
+            return;
+        }
+
+        tokio::task::spawn_blocking(move || {
+            hooks.run(event, activity.as_ref());
+            if let Some(activity) = &activity
+                && webhooks.is_enabled()
+            {
+                let delivered = Database::new().and_then(|db| {
+                    webhooks.send(&db, event, activity);
+                    webhooks.deliver_pending(&db, false)
+                });
+                if let Err(e) = delivered {
+                    warn!("⚠️  {e:#}");
+                }
+            }
+        });
+    }
+
+    // Avisa do que o inquérito registrou. Aberto pelo daemon, o processo do
+    // inquérito não executa hooks nem webhooks (ver `defer_notifications`).
+    fn notify_report(&self, report: &InquiryReport) {
+        let event = match report.outcome {
+            InquiryOutcome::New | InquiryOutcome::Backfilled => HookEvent::ActivitySaved,
+            InquiryOutcome::Continued => HookEvent::ActivityContinued,
+            InquiryOutcome::Skipped => {
+                self.notify(HookEvent::InquirySkipped, None);
+                return;
+            }
+            _ => return,
+        };
+        let hooks = Hooks::new(self.config.hooks.clone());
+        let webhooks = Webhooks::new(self.config.webhooks.clone());
+        if !hooks.is_set(event) && !webhooks.is_enabled() {
+            return;
+        }
+
+        let ids = report.activity_ids.clone();
+        tokio::task::spawn_blocking(move || {
+            let db = match Database::new() {
+                Ok(db) => db,
+                Err(e) => {
+                    warn!("⚠️  {e:#}");
+                    return;
+                }
+            };
+            for id in ids {
+                match db.get_activity(id) {
+                    Ok(Some(activity)) => {
+                        webhooks.send(&db, event, &activity);
+                        hooks.run(event, Some(&activity));
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("⚠️  Hook {} não executado: {e:#}", event.name()),
+                }
+            }
+            if let Err(e) = webhooks.deliver_pending(&db, false) {
+                warn!("⚠️  {e:#}");
+            }
+        });
+    }
+
+    // Entrega o que ficou na fila de webhooks (ex.: registrado sem conexão)
+    fn deliver_webhooks(&mut self) {
+        let webhooks = Webhooks::new(self.config.webhooks.clone());
+        let busy = self
+            .webhook_delivery
+            .as_ref()
+            .is_some_and(|task| !task.is_finished());
+        if busy || !webhooks.is_enabled() {
+            return;
+        }
+
+        self.webhook_delivery = Some(tokio::task::spawn_blocking(move || {
+            match Database::new().and_then(|db| webhooks.deliver_pending(&db, false)) {
+                Ok((delivered, _)) if delivered > 0 => {
+                    info!("🌐 {delivered} webhook(s) pendente(s) entregue(s)")
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️  {e:#}"),
+            }
+        }));
+    }
+
+    async fn finish_inquiry(&mut self, status: std::io::Result<ExitStatus>) {
+        let Some(mut inquiry) = self.inquiry.take() else {
+            return;
+        };
+        let number = inquiry.number;
+
+        // A janela informa o resultado numa linha JSON; o processo já terminou,
+        // então a leitura vai até o fim da saída sem bloquear
+        let mut stdout = String::new();
+        if let Some(mut pipe) = inquiry.child.stdout.take() {
+            let _ = pipe.read_to_string(&mut stdout).await;
+        }
+
+        // Um inquérito com erro não deve derrubar o daemon
+        let exit_code = match &status {
+            Ok(status) => status.code(),
+            Err(e) => {
+                warn!("⚠️  Erro ao aguardar processo de inquérito: {e}");
+                None
+            }
+        };
+        let report = InquiryReport::from_process(&stdout, exit_code);
+        match report.outcome {
+            InquiryOutcome::New | InquiryOutcome::Continued | InquiryOutcome::Backfilled => {
+                info!("✅ Inquérito #{number}: {}", report.outcome.label());
+            }
+            InquiryOutcome::Failed => warn!(
+                "⚠️  Inquérito #{number} falhou (código de saída: {})",
+                exit_code.map_or("nenhum".to_string(), |code| code.to_string())
+            ),
+            outcome => info!("🔕 Inquérito #{number}: {}", outcome.label()),
+        }
+        self.complete_inquiry(&inquiry, report, exit_code);
+    }
+
+    async fn kill_inquiry(&mut self, outcome: InquiryOutcome) {
+        let Some(mut inquiry) = self.inquiry.take() else {
+            return;
+        };
+
+        warn!("⚠️  Inquérito #{}: {}", inquiry.number, outcome.label());
+        let _ = inquiry.child.kill().await;
+
+        self.complete_inquiry(&inquiry, InquiryReport::new(outcome), None);
+    }
+
+    // Fim comum de um inquérito, tenha ele terminado sozinho ou sido encerrado:
+    // aplica a política de sem resposta, guarda o resultado e recalcula a agenda
+    fn complete_inquiry(
+        &mut self,
+        inquiry: &RunningInquiry,
+        mut report: InquiryReport,
+        exit_code: Option<i32>,
+    ) {
+        let number = inquiry.number;
+        let mut label = report.outcome.label().to_string();
+        // Expirado pelo daemon ou pelo próprio seletor (o modo `command` se
+        // encerra sozinho ao fim do tempo limite)
+        if report.outcome == InquiryOutcome::TimedOut {
+            self.apply_unanswered_policy(number, inquiry.started_at, &mut report, &mut label);
+        }
+        self.record_outcome(number, label);
+        store_outcome(&report, &inquiry.context, inquiry.started_at, exit_code);
+        self.notify_report(&report);
+
+        self.reschedule();
+        self.save_state();
+        self.print_next_inquiry();
+    }
+
+    // Registra o inquérito que expirou conforme `unanswered_policy`, juntando a
+    // atividade criada ao resultado
+    fn apply_unanswered_policy(
+        &self,
+        number: u32,
+        started_at: DateTime<Local>,
+        report: &mut InquiryReport,
+        label: &mut String,
+    ) {
+        let recorded = Database::new()
+            .and_then(|db| record_unanswered(&db, self.config.unanswered_policy, started_at));
+        match recorded {
+            Ok(Some(activity)) => {
+                info!("📝 Inquérito #{number}: {}", activity.kind.label());
+                *label = format!("{label}; {}", activity.kind.label());
+                report.activity_ids.extend(activity.id);
+                self.notify(HookEvent::ActivitySaved, Some(activity));
+            }
+            Ok(None) => {}
+            Err(e) => warn!("⚠️  Falha ao registrar inquérito sem resposta: {e:#}"),
+        }
+    }
+
+    // Espera o inquérito aberto terminar por até `grace` e então o encerra
+    async fn end_inquiry(&mut self, grace: Duration) {
+        let Some(inquiry) = self.inquiry.as_mut() else {
+            return;
+        };
+
+        match time::timeout(grace, inquiry.child.wait()).await {
+            Ok(status) => self.finish_inquiry(status).await,
+            Err(_) => self.kill_inquiry(InquiryOutcome::Interrupted).await,
+        }
+    }
+
+    fn save_state(&self) {
+        let state = DaemonState {
+            last_inquiry: self.last_inquiry,
+            inquiry_count: self.inquiry_count,
+            skipped_count: self.skipped_count,
+            saved_at: Some(Local::now()),
+        };
+        if let Err(e) = state.save() {
+            warn!("⚠️  {e:#}");
+        }
+    }
+
+    fn record_outcome(&mut self, number: u32, outcome: String) {
+        self.last_outcome = Some(format!(
+            "#{number} {outcome} às {}",
+            Local::now().format("%H:%M:%S")
+        ));
+    }
+
+    fn handle_control(&mut self, request: ControlRequest) -> ControlResponse {
+        match request {
+            ControlRequest::Status => ControlResponse {
+                ok: true,
+                message: String::new(),
+                status: Some(self.status()),
+            },
+            ControlRequest::Pause => {
+                if self.paused {
+                    return ControlResponse::ok("O daemon já estava pausado");
+                }
+                self.paused = true;
+                info!("⏸️  Inquéritos pausados");
+                ControlResponse::ok("Inquéritos pausados")
+            }
+            ControlRequest::Resume => {
+                if !self.paused {
+                    return ControlResponse::ok("O daemon não estava pausado");
+                }
+                self.paused = false;
+                self.reschedule();
+                info!("▶️  Inquéritos retomados");
+                self.print_next_inquiry();
+                ControlResponse::ok(format!(
+                    "Inquéritos retomados. Próximo: {}",
+                    self.next_due
+                        .map(format_next)
+                        .unwrap_or_else(|| "nenhum horário ativo na agenda".to_string())
+                ))
+            }
+            ControlRequest::AskNow => {
+                if self.inquiry.is_some() {
+                    return ControlResponse::error("Já existe um inquérito aberto");
+                }
+                if let Ok(Some(holder)) = instance::open_inquiry() {
+                    let _ = instance::request_inquiry_focus();
+                    return ControlResponse::ok(format!(
+                        "Já existe uma janela de inquérito aberta ({holder}); trazendo-a para frente"
+                    ));
+                }
+                self.start_inquiry(None, &PromptContext::manual(), None);
+                ControlResponse::ok("Inquérito aberto")
+            }
+            ControlRequest::Stop => ControlResponse::ok("Daemon encerrado"),
+        }
+    }
+
+    fn status(&self) -> control::DaemonStatus {
+        control::DaemonStatus {
+            pid: std::process::id(),
+            started_at: self.started_at,
+            inquiry_count: self.inquiry_count,
+            skipped_count: self.skipped_count,
+            paused: self.paused,
+            inquiry_running: self.inquiry.is_some(),
+            schedule: self.config.describe_schedule(),
+            next_inquiry: self.next_due.filter(|_| !self.paused),
+            last_outcome: self.last_outcome.clone(),
+        }
+    }
+
+    fn reload_config_if_changed(&mut self) {
+        let modified = AppConfig::modified_at();
+        if modified.is_none() || modified == self.config_modified {
+            return;
+        }
+        // Registrar a data mesmo em caso de erro, para não repetir o aviso a cada verificação
+        self.config_modified = modified;
+
+        let loaded = AppConfig::load().and_then(|config| {
+            let schedule = Schedule::from_config(&config)?;
+            Ok((config, schedule))
+        });
+        let (new_config, new_schedule) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                warn!("⚠️  Configuração alterada, mas não pôde ser lida: {e:#}");
+                warn!(
+                    "🔄 Mantendo a agenda atual ({})",
+                    self.config.describe_schedule()
+                );
+                return;
+            }
+        };
+
+        let changes = new_config.changes_from(&self.config);
+        if changes.is_empty() {
+            return;
+        }
+
+        info!("🔧 Configuração recarregada:");
+        for change in &changes {
+            info!("  • {change}");
+        }
+
+        let schedule_changed = new_config.describe_schedule() != self.config.describe_schedule();
+        self.config = new_config;
+
+        // Reagendar a partir do último inquérito, sem esperar a agenda antiga terminar
+        let previous_due = self.next_due;
+        self.schedule = new_schedule;
+        self.reschedule();
+
+        if schedule_changed {
+            info!("⏰ Nova agenda: {}", self.config.describe_schedule());
+        }
+        if self.next_due != previous_due {
+            self.print_next_inquiry();
+        }
+    }
+
+    fn reschedule(&mut self) {
+        self.next_due = self.schedule.next_inquiry(self.last_inquiry, Local::now());
+        debug!(
+            "Agenda recalculada: último inquérito {:?}, próximo {:?}",
+            self.last_inquiry, self.next_due
+        );
+    }
+
+    fn print_next_inquiry(&self) {
+        if self.paused {
+            info!("📅 Próximo inquérito: pausado");
+            return;
+        }
+        match self.next_due {
+            Some(next) => info!("📅 Próximo inquérito em: {}", format_next(next)),
+            None => info!("⚠️  Nenhum horário ativo na agenda; inquéritos suspensos"),
+        }
+    }
+}
+
+fn duration_until(at: DateTime<Local>) -> Duration {
+    (at - Local::now()).to_std().unwrap_or(Duration::ZERO)
+}
+
+// Executa o inquérito num processo separado, para que um problema na janela
+// não derrube o daemon
+fn spawn_inquiry(
+    context: &PromptContext,
+    backfill_since: Option<DateTime<Local>>,
+    prompter: PrompterKind,
+) -> Result<Child> {
+    let current_exe = std::env::current_exe()
+        .map_err(|e| anyhow::anyhow!("Não foi possível obter caminho do executável: {e}"))?;
+
+    debug!("🚀 Iniciando inquérito em processo separado: {current_exe:?}");
+
+    Command::new(&current_exe)
+        .args(options::forwarded_args())
+        .arg("inquiry")
+        .arg("--report")
+        .arg("--from-daemon")
+        .arg("--prompter")
+        .arg(prompter.as_str())
+        .arg("--schedule-mode")
+        .arg(&context.schedule_mode)
+        .args(
+            context
+                .planned_at
+                .map(|planned| ["--planned-at".to_string(), planned.to_rfc3339()])
+                .into_iter()
+                .flatten(),
+        )
+        .args(
+            backfill_since
+                .map(|since| {
+                    [
+                        "--backfill".to_string(),
+                        "--since".to_string(),
+                        since.to_rfc3339(),
+                    ]
+                })
+                .into_iter()
+                .flatten(),
+        )
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Erro ao iniciar processo de inquérito: {e}"))
+}
+
+async fn wait_inquiry(inquiry: &mut Option<RunningInquiry>) -> std::io::Result<ExitStatus> {
+    match inquiry {
+        Some(inquiry) => inquiry.child.wait().await,
+        None => std::future::pending().await,
     }
 }
 
-pub fn print_daemon_info() {
-    println!("📊 Informações do Daemon:");
-    println!("  • Intervalo padrão: 60 minutos (1 hora)");
-    println!("  • Primeiro inquérito: Imediato");
-    println!("  • Inquéritos subsequentes: A cada hora");
-    println!("  • Persistência: Salva no banco SQLite");
-    println!("  • Localização do banco: ~/.config/activity-inquirer/");
-    println!();
+// Guarda o resultado na tabela `prompt_outcomes`; uma falha no banco só é registrada no log
+fn store_outcome(
+    report: &InquiryReport,
+    context: &PromptContext,
+    started_at: DateTime<Local>,
+    exit_code: Option<i32>,
+) {
+    let stored = Database::new()
+        .and_then(|db| db.record_prompt_outcome(report, context, started_at, exit_code));
+    if let Err(e) = stored {
+        warn!("⚠️  {e:#}");
+    }
+}
+
+// Aplica `unanswered_policy` a um inquérito que expirou, registrando a partir
+// do horário em que ele foi aberto. Devolve a atividade criada, se houver.
+fn record_unanswered(
+    db: &Database,
+    policy: UnansweredPolicy,
+    at: DateTime<Local>,
+) -> Result<Option<Activity>> {
+    let (input, kind) = match policy {
+        UnansweredPolicy::Nothing => return Ok(None),
+        UnansweredPolicy::Away => (
+            ActivityInput {
+                description: "Ausente".to_string(),
+                ..ActivityInput::default()
+            },
+            ActivityKind::Away,
+        ),
+        // Sem atividade hoje, ou já ausente, não há o que repetir
+        UnansweredPolicy::RepeatLast => match db.get_last_activity_today()? {
+            Some(last) if last.kind != ActivityKind::Away => (
+                ActivityInput {
+                    description: last.description,
+                    project: last.project.map(|project| project.name),
+                    tags: last.tags,
+                },
+                ActivityKind::Repeated,
+            ),
+            _ => return Ok(None),
+        },
+    };
+
+    let id = db.add_unanswered_activity(&input, at, kind)?;
+    db.get_activity(id)
+}
+
+// SIGINT (Ctrl+C) e, no Unix, SIGTERM (ex.: `systemctl --user stop`)
+struct ShutdownSignals {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl ShutdownSignals {
+    fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                .context("Falha ao registrar tratamento de SIGTERM")?,
+        })
+    }
+
+    #[cfg(unix)]
+    async fn recv(&mut self) -> &'static str {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => "SIGINT",
+            _ = self.terminate.recv() => "SIGTERM",
+        }
+    }
+
+    #[cfg(not(unix))]
+    async fn recv(&mut self) -> &'static str {
+        match tokio::signal::ctrl_c().await {
+            Ok(()) => "Ctrl+C",
+            Err(_) => std::future::pending().await,
+        }
+    }
+}
+
+async fn recv_control(control: &mut Option<ControlServer>) -> Option<ControlMessage> {
+    match control {
+        Some(server) => server.receiver.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+pub async fn run_daemon() -> Result<()> {
+    // Carregar configuração
+    let config = AppConfig::load()?;
+    let control = control::listen()?;
+
+    info!("🤖 Iniciando modo daemon - Activity Inquirer");
+    info!("⏰ Agenda configurada: {}", config.describe_schedule());
+    if config.schedule.enabled {
+        info!("🗓️  Horário de trabalho ativo: fora dele, o daemon aguarda a próxima janela");
+    }
+    info!("💡 Alterações em config.toml são aplicadas automaticamente");
+    if control.is_some() {
+        info!("💡 Controle: acv-inq daemon status|pause|resume|ask-now|stop");
+    }
+    info!("💡 Pressione Ctrl+C para parar o daemon");
+
+    let state = DaemonState::load().unwrap_or_else(|e| {
+        warn!("⚠️  {e:#}; começando do zero");
+        DaemonState::default()
+    });
+    if let Some(last) = state.last_inquiry {
+        info!(
+            "📂 Estado anterior restaurado: último inquérito em {} ({} realizado(s))",
+            last.format("%d/%m/%Y %H:%M:%S"),
+            state.inquiry_count
+        );
+    }
+
+    // No modo de intervalo, o primeiro inquérito conta a partir do último da
+    // execução anterior (sem nenhum, é imediato, dentro do horário ativo); nos
+    // demais modos, espera o próximo horário da agenda
+    let mut daemon = Daemon::new(config, state)?;
+    if daemon.next_due.is_some_and(|next| next > Local::now()) {
+        daemon.print_next_inquiry();
+    }
+    daemon.notify(HookEvent::DaemonStart, None);
+    daemon.run(control).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+
+    fn daemon(interval_minutes: u64) -> Daemon {
+        let config = AppConfig {
+            daemon_interval_minutes: interval_minutes,
+            ..AppConfig::default()
+        };
+        Daemon::new(config, DaemonState::default()).unwrap()
+    }
+
+    // Avança o relógio em `wall` e o tempo monotônico em `monotonic` desde a
+    // última volta do laço
+    fn tick(daemon: &mut Daemon, wall: chrono::Duration, monotonic: Duration) {
+        let now = daemon.heartbeat + wall;
+        let instant = daemon.heartbeat_monotonic + monotonic;
+        daemon.check_clock_jump_at(now, instant);
+    }
+
+    #[test]
+    fn clock_steps_below_the_interval_are_not_suspensions() {
+        let mut daemon = daemon(15);
+
+        // Ajuste de alguns minutos (NTP, mudança manual)
+        tick(
+            &mut daemon,
+            chrono::Duration::minutes(3),
+            Duration::from_secs(5),
+        );
+        // Laço ocupado por muito tempo, com os dois relógios juntos
+        tick(
+            &mut daemon,
+            chrono::Duration::hours(2),
+            Duration::from_secs(7200),
+        );
+        // Relógio atrasado
+        tick(
+            &mut daemon,
+            chrono::Duration::hours(-1),
+            Duration::from_secs(5),
+        );
+
+        assert_eq!(daemon.pending_backfill, None);
+    }
+
+    #[test]
+    fn suspension_longer_than_the_interval_asks_for_backfill() {
+        let mut daemon = daemon(15);
+        let since = daemon.heartbeat;
+
+        tick(
+            &mut daemon,
+            chrono::Duration::minutes(16),
+            Duration::from_secs(5),
+        );
+
+        assert_eq!(daemon.pending_backfill, Some(since));
+        assert_eq!(daemon.heartbeat, since + chrono::Duration::minutes(16));
+
+        // Uma segunda suspensão mantém o início da primeira
+        tick(
+            &mut daemon,
+            chrono::Duration::hours(1),
+            Duration::from_secs(5),
+        );
+        assert_eq!(daemon.pending_backfill, Some(since));
+    }
+
+    #[test]
+    fn short_intervals_still_need_the_minimum_jump() {
+        let mut daemon = daemon(1);
+
+        tick(&mut daemon, chrono::Duration::seconds(50), Duration::ZERO);
+        assert_eq!(daemon.pending_backfill, None);
+
+        let since = daemon.heartbeat;
+        tick(
+            &mut daemon,
+            chrono::Duration::seconds(90),
+            Duration::from_secs(5),
+        );
+        assert_eq!(daemon.pending_backfill, Some(since));
+    }
+
+    // Inquérito aberto há 20 minutos, depois de uma atividade respondida
+    fn answered_then_expired(test: &TestDb) -> (Activity, DateTime<Local>) {
+        let now = Local::now();
+        let input = ActivityInput::parse("revisão @cliente #pr");
+        let id = test
+            .db
+            .add_activity_at(&input, now - chrono::Duration::minutes(40))
+            .unwrap();
+        (
+            test.db.get_activity(id).unwrap().unwrap(),
+            now - chrono::Duration::minutes(20),
+        )
+    }
+
+    #[test]
+    fn nothing_policy_records_nothing() {
+        let test = TestDb::new("daemon-unanswered-nothing");
+        let (last, at) = answered_then_expired(&test);
+
+        let recorded = record_unanswered(&test.db, UnansweredPolicy::Nothing, at).unwrap();
+
+        assert!(recorded.is_none());
+        assert_eq!(test.db.get_last_activity().unwrap().unwrap().id, last.id);
+    }
+
+    #[test]
+    fn away_policy_records_absence_from_inquiry_start() {
+        let test = TestDb::new("daemon-unanswered-away");
+        let (last, at) = answered_then_expired(&test);
+
+        let away = record_unanswered(&test.db, UnansweredPolicy::Away, at)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(away.description, "Ausente");
+        assert_eq!(away.kind, ActivityKind::Away);
+        assert_eq!(away.timestamp, at);
+        assert_eq!(away.responses, 0);
+        assert_eq!(away.project, None);
+        // A atividade anterior termina quando o inquérito foi aberto
+        let last = test.db.get_activity(last.id.unwrap()).unwrap().unwrap();
+        assert_eq!(last.end_timestamp, at);
+    }
+
+    #[test]
+    fn repeat_policy_copies_last_activity() {
+        let test = TestDb::new("daemon-unanswered-repeat");
+        let (last, at) = answered_then_expired(&test);
+
+        let repeated = record_unanswered(&test.db, UnansweredPolicy::RepeatLast, at)
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(repeated.id, last.id);
+        assert_eq!(repeated.description, last.description);
+        assert_eq!(repeated.project, last.project);
+        assert_eq!(repeated.tags, last.tags);
+        assert_eq!(repeated.kind, ActivityKind::Repeated);
+        assert_eq!(repeated.timestamp, at);
+        assert_eq!(repeated.responses, 0);
+    }
+
+    #[test]
+    fn repeat_policy_needs_an_activity_to_repeat() {
+        // Nada registrado hoje
+        let test = TestDb::new("daemon-unanswered-repeat-empty");
+        let at = Local::now();
+        assert!(
+            record_unanswered(&test.db, UnansweredPolicy::RepeatLast, at)
+                .unwrap()
+                .is_none()
+        );
+
+        // Depois de um período ausente, a ausência não é repetida
+        let test = TestDb::new("daemon-unanswered-repeat-away");
+        let (_, at) = answered_then_expired(&test);
+        record_unanswered(&test.db, UnansweredPolicy::Away, at).unwrap();
+        let later = at + chrono::Duration::minutes(10);
+        assert!(
+            record_unanswered(&test.db, UnansweredPolicy::RepeatLast, later)
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(
+            test.db.get_last_activity().unwrap().unwrap().kind,
+            ActivityKind::Away
+        );
+    }
+
+    #[test]
+    fn state_saved_by_older_versions_still_loads() {
+        let state: DaemonState = serde_json::from_str(
+            r#"{
+                "last_inquiry": "2026-03-02T10:00:00-03:00",
+                "next_due": "2026-03-02T11:00:00-03:00",
+                "inquiry_count": 7
+            }"#,
+        )
+        .unwrap();
+
+        assert!(state.last_inquiry.is_some());
+        assert_eq!(state.inquiry_count, 7);
+        assert_eq!(state.skipped_count, 0);
+    }
 }
@@ -1,33 +1,62 @@
 use anyhow::{Context, Result};
 use chrono::{DateTime, Local, NaiveDate};
-use rusqlite::{Connection, Row, params};
-use std::path::PathBuf;
+use rusqlite::{Connection, OptionalExtension, Row, params};
+use std::path::{Path, PathBuf};
 
-use crate::models::Activity;
+use crate::migrations;
+use crate::models::{
+    Activity, ActivityInput, ActivityKind, DailySummary, DateRange, DescriptionTotal,
+    InquiryReport, Project, PromptContext, ResponseCounts, ResponseKind, SearchResult,
+    WebhookDelivery, WebhookQueue,
+};
+use crate::options;
 
 pub struct Database {
     conn: Connection,
 }
 
+// Colunas lidas por `row_to_activity`, na ordem esperada
+const ACTIVITY_COLUMNS: &str = "a.id, a.description, a.timestamp, a.date, a.end_timestamp,
+        a.responses, p.id, p.name, p.color, p.icon,
+        (SELECT group_concat(t.name, ',') FROM activity_tags at
+            JOIN tags t ON t.id = at.tag_id
+            WHERE at.activity_id = a.id),
+        a.kind";
+
+const ACTIVITY_FROM: &str = "FROM activities a LEFT JOIN projects p ON p.id = a.project_id";
+
+// Os horários são gravados em RFC 3339 com o fuso local, que muda no horário de
+// verão; por isso a ordem é a do instante que o texto representa, e não a do texto
+const ACTIVITY_START: &str = "unixepoch(a.timestamp, 'subsec')";
+
 impl Database {
     pub fn new() -> Result<Self> {
-        let db_path = Self::get_db_path()?;
+        Self::open(&Self::get_db_path()?)
+    }
 
+    pub fn open(db_path: &Path) -> Result<Self> {
         // Criar diretório se não existir
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)
                 .context("Falha ao criar diretório do banco de dados")?;
         }
 
-        let conn =
-            Connection::open(&db_path).context("Falha ao abrir conexão com o banco de dados")?;
+        let mut conn =
+            Connection::open(db_path).context("Falha ao abrir conexão com o banco de dados")?;
+
+        conn.pragma_update(None, "foreign_keys", true)
+            .context("Falha ao habilitar chaves estrangeiras")?;
 
-        let db = Database { conn };
-        db.initialize_tables()?;
-        Ok(db)
+        migrations::run(&mut conn, db_path)?;
+
+        Ok(Database { conn })
     }
 
-    fn get_db_path() -> Result<PathBuf> {
+    pub fn get_db_path() -> Result<PathBuf> {
+        if let Some(path) = &options::get().db {
+            return Ok(path.clone());
+        }
+
         let config_dir = if cfg!(target_os = "windows") {
             // Windows: %APPDATA%\activity-inquirer
             dirs::config_dir()
@@ -45,40 +74,394 @@ impl Database {
         Ok(config_dir.join("activity-inquirer").join("activities.db"))
     }
 
-    fn initialize_tables(&self) -> Result<()> {
+    // Registra uma nova atividade começando agora. A atividade anterior do dia,
+    // se houver, é encerrada neste mesmo instante.
+    pub fn add_activity(&self, input: &ActivityInput) -> Result<i64> {
+        self.add_activity_at(input, Local::now())
+    }
+
+    // Registra uma atividade começando em `at`, que pode estar no passado.
+    // A atividade que estava em andamento naquele momento é encerrada em `at`.
+    pub fn add_activity_at(&self, input: &ActivityInput, at: DateTime<Local>) -> Result<i64> {
+        self.insert_activity(input, at, ActivityKind::Answered)
+    }
+
+    // Registra no lugar de um inquérito que expirou sem resposta. Atividades
+    // automáticas não contam como respostas.
+    pub fn add_unanswered_activity(
+        &self,
+        input: &ActivityInput,
+        at: DateTime<Local>,
+        kind: ActivityKind,
+    ) -> Result<i64> {
+        self.insert_activity(input, at, kind)
+    }
+
+    fn insert_activity(
+        &self,
+        input: &ActivityInput,
+        at: DateTime<Local>,
+        kind: ActivityKind,
+    ) -> Result<i64> {
+        let date = at.date_naive();
+
+        // Encerrar a anterior, inserir e gravar as tags juntos: uma falha no meio
+        // não pode deixar dois intervalos sobrepostos ou um sem fim
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .context("Falha ao iniciar transação")?;
+
+        let same_day = self.get_activities_between(DateRange::day(date))?;
+
+        if let Some(previous) = same_day
+            .iter()
+            .filter(|activity| activity.timestamp <= at)
+            .max_by_key(|activity| activity.timestamp)
+            && let Some(previous_id) = previous.id
+            && previous.end_timestamp != at
+        {
+            self.conn
+                .execute(
+                    "UPDATE activities SET end_timestamp = ?1 WHERE id = ?2",
+                    params![at.to_rfc3339(), previous_id],
+                )
+                .context("Falha ao encerrar atividade anterior")?;
+        }
+
+        // Ao registrar no passado, a nova atividade vai até o início da seguinte
+        let end = same_day
+            .iter()
+            .map(|activity| activity.timestamp)
+            .filter(|start| *start > at)
+            .min()
+            .unwrap_or(at);
+
+        let project_id = self.resolve_project(input.project.as_deref())?;
+
         self.conn
             .execute(
-                "CREATE TABLE IF NOT EXISTS activities (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                description TEXT NOT NULL,
-                timestamp TEXT NOT NULL,
-                date TEXT NOT NULL
-            )",
-                [],
+                "INSERT INTO activities (description, timestamp, end_timestamp, responses, date, project_id, kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    input.description,
+                    at.to_rfc3339(),
+                    end.to_rfc3339(),
+                    if kind.is_automatic() { 0 } else { 1 },
+                    date.to_string(),
+                    project_id,
+                    kind.as_str()
+                ],
             )
-            .context("Falha ao criar tabela de atividades")?;
+            .context("Falha ao inserir atividade")?;
 
-        Ok(())
+        let id = self.conn.last_insert_rowid();
+        self.set_activity_tags(id, &input.tags)?;
+
+        tx.commit()
+            .context("Falha ao confirmar registro da atividade")?;
+        Ok(id)
     }
 
-    pub fn add_activity(&self, description: String) -> Result<()> {
+    pub fn get_activity(&self, id: i64) -> Result<Option<Activity>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {ACTIVITY_COLUMNS} {ACTIVITY_FROM} WHERE a.id = ?1"
+            ))
+            .context("Falha ao preparar consulta")?;
+
+        let mut activity_iter = stmt
+            .query_map([id], |row| self.row_to_activity(row))
+            .context("Falha ao executar consulta")?;
+
+        match activity_iter.next() {
+            Some(activity) => Ok(Some(activity?)),
+            None => Ok(None),
+        }
+    }
+
+    // Estende a atividade até agora, sem criar um novo registro
+    pub fn continue_activity(&self, id: i64) -> Result<()> {
         let now = Local::now();
-        let date = now.date_naive();
 
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE activities SET end_timestamp = ?1, responses = responses + 1 WHERE id = ?2",
+                params![now.to_rfc3339(), id],
+            )
+            .context("Falha ao estender atividade")?;
+
+        if updated == 0 {
+            anyhow::bail!("Atividade {id} não encontrada");
+        }
+
+        Ok(())
+    }
+
+    pub fn record_prompt_response(
+        &self,
+        activity_id: i64,
+        kind: ResponseKind,
+        context: &PromptContext,
+    ) -> Result<()> {
         self.conn
             .execute(
-                "INSERT INTO activities (description, timestamp, date) VALUES (?1, ?2, ?3)",
-                params![description, now.to_rfc3339(), date.to_string()],
+                "INSERT INTO prompt_responses (activity_id, kind, schedule_mode, planned_at, answered_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    activity_id,
+                    kind.as_str(),
+                    context.schedule_mode,
+                    context.planned_at.map(|planned| planned.to_rfc3339()),
+                    Local::now().to_rfc3339()
+                ],
             )
-            .context("Falha ao inserir atividade")?;
+            .context("Falha ao registrar resposta ao inquérito")?;
+
+        Ok(())
+    }
+
+    pub fn record_prompt_outcome(
+        &self,
+        report: &InquiryReport,
+        context: &PromptContext,
+        started_at: DateTime<Local>,
+        exit_code: Option<i32>,
+    ) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO prompt_outcomes
+                    (outcome, schedule_mode, planned_at, started_at, finished_at, exit_code, activity_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    report.outcome.as_str(),
+                    context.schedule_mode,
+                    context.planned_at.map(|planned| planned.to_rfc3339()),
+                    started_at.to_rfc3339(),
+                    Local::now().to_rfc3339(),
+                    exit_code,
+                    report.activity_ids.last()
+                ],
+            )
+            .context("Falha ao registrar resultado do inquérito")?;
+
+        Ok(())
+    }
+
+    // Inquéritos do daemon desde `since` e quantos deles foram respondidos
+    pub fn response_rate(&self, since: DateTime<Local>) -> Result<(i64, i64)> {
+        self.conn
+            .query_row(
+                "SELECT COUNT(*),
+                    coalesce(SUM(outcome IN ('new', 'continued', 'backfilled')), 0)
+                 FROM prompt_outcomes WHERE unixepoch(started_at) >= ?1",
+                [since.timestamp()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("Falha ao calcular taxa de resposta")
+    }
+
+    pub fn enqueue_webhook(&self, url: &str, event: &str, payload: &str) -> Result<i64> {
+        let now = Local::now().to_rfc3339();
+        self.conn
+            .execute(
+                "INSERT INTO webhook_outbox (url, event, payload, created_at, next_attempt_at)
+                 VALUES (?1, ?2, ?3, ?4, ?4)",
+                params![url, event, payload, now],
+            )
+            .context("Falha ao enfileirar webhook")?;
+
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    // Entregas ainda não feitas e não reservadas por outro processo, em ordem de
+    // registro. Sem `force`, só as que já podem ser tentadas de novo e não
+    // esgotaram as tentativas.
+    pub fn pending_webhooks(
+        &self,
+        max_attempts: u32,
+        force: bool,
+        now: DateTime<Local>,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT id, url, event, payload, attempts FROM webhook_outbox
+                 WHERE delivered_at IS NULL
+                   AND (leased_until IS NULL OR leased_until <= ?3)
+                   AND (?1 OR (attempts < ?2 AND unixepoch(next_attempt_at) <= ?3))
+                 ORDER BY id",
+            )
+            .context("Falha ao preparar consulta")?;
+
+        let deliveries = stmt
+            .query_map(params![force, max_attempts, now.timestamp()], |row| {
+                Ok(WebhookDelivery {
+                    id: row.get(0)?,
+                    url: row.get(1)?,
+                    event: row.get(2)?,
+                    payload: row.get(3)?,
+                    attempts: row.get(4)?,
+                })
+            })
+            .context("Falha ao executar consulta")?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Falha ao ler fila de webhooks")?;
+
+        Ok(deliveries)
+    }
+
+    // Reserva a entrega até `until`, para que outro processo (a janela de
+    // inquérito, o daemon ou o `webhooks flush`) não a envie ao mesmo tempo.
+    // `false` se outro processo a reservou ou entregou primeiro.
+    pub fn claim_webhook(
+        &self,
+        id: i64,
+        now: DateTime<Local>,
+        until: DateTime<Local>,
+    ) -> Result<bool> {
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE webhook_outbox SET leased_until = ?1
+                 WHERE id = ?2 AND delivered_at IS NULL
+                   AND (leased_until IS NULL OR leased_until <= ?3)",
+                params![until.timestamp(), id, now.timestamp()],
+            )
+            .context("Falha ao reservar entrega de webhook")?;
+
+        Ok(updated == 1)
+    }
+
+    pub fn webhook_delivered(&self, id: i64, at: DateTime<Local>) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE webhook_outbox SET attempts = attempts + 1, delivered_at = ?1,
+                     last_error = NULL, leased_until = NULL
+                 WHERE id = ?2",
+                params![at.to_rfc3339(), id],
+            )
+            .context("Falha ao registrar entrega de webhook")?;
+
+        Ok(())
+    }
+
+    pub fn webhook_failed(&self, id: i64, error: &str, retry_at: DateTime<Local>) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE webhook_outbox SET attempts = attempts + 1, last_error = ?1,
+                     next_attempt_at = ?2, leased_until = NULL
+                 WHERE id = ?3",
+                params![error, retry_at.to_rfc3339(), id],
+            )
+            .context("Falha ao registrar falha de webhook")?;
+
+        Ok(())
+    }
+
+    pub fn webhook_queue(&self, max_attempts: u32) -> Result<WebhookQueue> {
+        let (pending, abandoned) = self
+            .conn
+            .query_row(
+                "SELECT coalesce(SUM(attempts < ?1), 0), coalesce(SUM(attempts >= ?1), 0)
+                 FROM webhook_outbox WHERE delivered_at IS NULL",
+                [max_attempts],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("Falha ao consultar fila de webhooks")?;
+
+        let last_error = self
+            .conn
+            .query_row(
+                "SELECT last_error FROM webhook_outbox
+                 WHERE delivered_at IS NULL AND last_error IS NOT NULL
+                 ORDER BY unixepoch(next_attempt_at) DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("Falha ao consultar fila de webhooks")?;
+
+        Ok(WebhookQueue {
+            pending,
+            abandoned,
+            last_error,
+        })
+    }
+
+    // Uma atividade automática editada pelo usuário passa a valer como resposta
+    pub fn update_activity(
+        &self,
+        id: i64,
+        input: &ActivityInput,
+        timestamp: DateTime<Local>,
+        end_timestamp: DateTime<Local>,
+    ) -> Result<()> {
+        if end_timestamp < timestamp {
+            anyhow::bail!("O fim da atividade não pode ser anterior ao início");
+        }
+
+        let date = timestamp.date_naive();
+
+        // Projeto, atividade e tags mudam juntos ou não mudam
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .context("Falha ao iniciar transação")?;
+
+        let project_id = self.resolve_project(input.project.as_deref())?;
+
+        // Uma atividade automática editada passa a contar como resposta, e
+        // `response_counts` precisa dela nas respostas também
+        let updated = self
+            .conn
+            .execute(
+                "UPDATE activities SET description = ?1, timestamp = ?2, end_timestamp = ?3, date = ?4,
+                 project_id = ?5, kind = 'answered', responses = max(responses, 1) WHERE id = ?6",
+                params![
+                    input.description,
+                    timestamp.to_rfc3339(),
+                    end_timestamp.to_rfc3339(),
+                    date.to_string(),
+                    project_id,
+                    id
+                ],
+            )
+            .context("Falha ao atualizar atividade")?;
+
+        if updated == 0 {
+            anyhow::bail!("Atividade {id} não encontrada");
+        }
+
+        self.set_activity_tags(id, &input.tags)?;
+
+        tx.commit()
+            .context("Falha ao confirmar edição da atividade")?;
+        Ok(())
+    }
+
+    pub fn delete_activity(&self, id: i64) -> Result<()> {
+        let deleted = self
+            .conn
+            .execute("DELETE FROM activities WHERE id = ?1", [id])
+            .context("Falha ao remover atividade")?;
+
+        if deleted == 0 {
+            anyhow::bail!("Atividade {id} não encontrada");
+        }
 
         Ok(())
     }
 
     pub fn get_activities_for_date(&self, date: NaiveDate) -> Result<Vec<Activity>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, description, timestamp, date FROM activities WHERE date = ?1 ORDER BY timestamp"
-        ).context("Falha ao preparar consulta")?;
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {ACTIVITY_COLUMNS} {ACTIVITY_FROM} WHERE a.date = ?1 ORDER BY {ACTIVITY_START}, a.id"
+            ))
+            .context("Falha ao preparar consulta")?;
 
         let activity_iter = stmt
             .query_map([date.to_string()], |row| self.row_to_activity(row))
@@ -86,19 +469,142 @@ impl Database {
 
         let mut activities = Vec::new();
         for activity in activity_iter {
-            println!("Atividade: {activity:?}");
             activities.push(activity?);
         }
 
         Ok(activities)
     }
 
+    // Atividades de um intervalo de datas; use `DateRange::iso_week` e
+    // `DateRange::month` para consultar semanas e meses
+    pub fn get_activities_between(&self, range: DateRange) -> Result<Vec<Activity>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {ACTIVITY_COLUMNS} {ACTIVITY_FROM}
+                 WHERE a.date BETWEEN ?1 AND ?2 ORDER BY {ACTIVITY_START}, a.id"
+            ))
+            .context("Falha ao preparar consulta")?;
+
+        let activities = stmt
+            .query_map([range.start.to_string(), range.end.to_string()], |row| {
+                self.row_to_activity(row)
+            })
+            .context("Falha ao executar consulta")?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Falha ao ler atividades")?;
+
+        Ok(activities)
+    }
+
+    // Tempo total por descrição, do maior para o menor
+    pub fn time_per_description(&self, range: DateRange) -> Result<Vec<DescriptionTotal>> {
+        let mut totals: Vec<DescriptionTotal> = Vec::new();
+
+        for activity in self.get_activities_between(range)? {
+            let seconds = activity.duration().num_seconds();
+            match totals
+                .iter_mut()
+                .find(|total| total.description == activity.description)
+            {
+                Some(total) => {
+                    total.entries += 1;
+                    total.responses += activity.responses;
+                    total.total_seconds += seconds;
+                }
+                None => totals.push(DescriptionTotal {
+                    description: activity.description,
+                    entries: 1,
+                    responses: activity.responses,
+                    total_seconds: seconds,
+                }),
+            }
+        }
+
+        totals.sort_by(|a, b| {
+            b.total_seconds
+                .cmp(&a.total_seconds)
+                .then_with(|| a.description.cmp(&b.description))
+        });
+        Ok(totals)
+    }
+
+    // Um resumo por dia com registros: quantidade, tempo e primeiro/último horário
+    pub fn daily_summaries(&self, range: DateRange) -> Result<Vec<DailySummary>> {
+        let mut summaries: Vec<DailySummary> = Vec::new();
+
+        for activity in self.get_activities_between(range)? {
+            let seconds = activity.duration().num_seconds();
+            match summaries.last_mut() {
+                Some(summary) if summary.date == activity.date => {
+                    summary.entries += 1;
+                    summary.responses += activity.responses;
+                    summary.total_seconds += seconds;
+                    summary.first_start = summary.first_start.min(activity.timestamp);
+                    summary.last_end = summary.last_end.max(activity.end_timestamp);
+                }
+                _ => summaries.push(DailySummary {
+                    date: activity.date,
+                    entries: 1,
+                    responses: activity.responses,
+                    total_seconds: seconds,
+                    first_start: activity.timestamp,
+                    last_end: activity.end_timestamp,
+                }),
+            }
+        }
+
+        Ok(summaries)
+    }
+
+    pub fn response_counts(&self, range: DateRange) -> Result<ResponseCounts> {
+        let (new_entries, responses): (u32, u32) = self
+            .conn
+            .query_row(
+                "SELECT coalesce(SUM(kind = 'answered'), 0), coalesce(SUM(responses), 0)
+                 FROM activities WHERE date BETWEEN ?1 AND ?2",
+                [range.start.to_string(), range.end.to_string()],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .context("Falha ao contar respostas")?;
+
+        Ok(ResponseCounts {
+            new_entries,
+            continuations: responses.saturating_sub(new_entries),
+        })
+    }
+
+    // Atividade mais recente de qualquer data, pelo fim do intervalo
+    pub fn get_last_activity(&self) -> Result<Option<Activity>> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {ACTIVITY_COLUMNS} {ACTIVITY_FROM}
+                 ORDER BY unixepoch(coalesce(a.end_timestamp, a.timestamp), 'subsec') DESC, a.id DESC
+                 LIMIT 1"
+            ))
+            .context("Falha ao preparar consulta")?;
+
+        let mut activity_iter = stmt
+            .query_map([], |row| self.row_to_activity(row))
+            .context("Falha ao executar consulta")?;
+
+        match activity_iter.next() {
+            Some(activity) => Ok(Some(activity?)),
+            None => Ok(None),
+        }
+    }
+
     pub fn get_last_activity_today(&self) -> Result<Option<Activity>> {
         let today = Local::now().date_naive();
 
-        let mut stmt = self.conn.prepare(
-            "SELECT id, description, timestamp, date FROM activities WHERE date = ?1 ORDER BY timestamp DESC LIMIT 1"
-        ).context("Falha ao preparar consulta")?;
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {ACTIVITY_COLUMNS} {ACTIVITY_FROM} WHERE a.date = ?1
+                 ORDER BY {ACTIVITY_START} DESC, a.id DESC LIMIT 1"
+            ))
+            .context("Falha ao preparar consulta")?;
 
         let mut activity_iter = stmt
             .query_map([today.to_string()], |row| self.row_to_activity(row))
@@ -126,28 +632,550 @@ impl Database {
         Ok(count)
     }
 
+    // Busca textual em descrição, projeto e tags de todas as datas.
+    // Aceita frases entre aspas ("revisão de código") e prefixos (relat*).
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<SearchResult>> {
+        let Some(fts_query) = Self::build_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {ACTIVITY_COLUMNS},
+                    snippet(activities_fts, 0, '«', '»', '…', 12)
+                 FROM activities_fts
+                 JOIN activities a ON a.id = activities_fts.rowid
+                 LEFT JOIN projects p ON p.id = a.project_id
+                 WHERE activities_fts MATCH ?1
+                 ORDER BY activities_fts.rank, {ACTIVITY_START} DESC
+                 LIMIT ?2"
+            ))
+            .context("Falha ao preparar busca")?;
+
+        let results = stmt
+            .query_map(params![fts_query, limit as i64], |row| {
+                Ok(SearchResult {
+                    activity: self.row_to_activity(row)?,
+                    snippet: row.get(12)?,
+                })
+            })
+            .context("Falha ao executar busca")?
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .context("Falha ao ler resultados da busca")?;
+
+        Ok(results)
+    }
+
+    // Converte o texto digitado em uma consulta FTS5 segura: cada palavra vira um
+    // termo entre aspas (o `*` final é mantido como prefixo) e frases entre aspas
+    // são preservadas. Os termos são combinados com AND.
+    fn build_fts_query(query: &str) -> Option<String> {
+        let mut terms = Vec::new();
+
+        for (index, part) in query.split('"').enumerate() {
+            if index % 2 == 1 {
+                // Dentro de aspas: frase exata
+                let phrase = part.split_whitespace().collect::<Vec<_>>().join(" ");
+                if !phrase.is_empty() {
+                    terms.push(format!("\"{phrase}\""));
+                }
+                continue;
+            }
+
+            for word in part.split_whitespace() {
+                let is_prefix = word.ends_with('*');
+                let word: String = word
+                    .chars()
+                    .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                    .collect();
+                if word.is_empty() {
+                    continue;
+                }
+                terms.push(if is_prefix {
+                    format!("\"{word}\"*")
+                } else {
+                    format!("\"{word}\"")
+                });
+            }
+        }
+
+        if terms.is_empty() {
+            None
+        } else {
+            Some(terms.join(" "))
+        }
+    }
+
+    pub fn list_projects(&self) -> Result<Vec<Project>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, name, color, icon FROM projects ORDER BY name COLLATE NOCASE")
+            .context("Falha ao preparar consulta de projetos")?;
+
+        let projects = stmt
+            .query_map([], |row| {
+                Ok(Project {
+                    id: Some(row.get(0)?),
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    icon: row.get(3)?,
+                })
+            })
+            .context("Falha ao listar projetos")?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(projects)
+    }
+
+    pub fn update_project(&self, project: &Project) -> Result<()> {
+        let Some(id) = project.id else {
+            anyhow::bail!("Projeto sem identificador");
+        };
+
+        self.conn
+            .execute(
+                "UPDATE projects SET name = ?1, color = ?2, icon = ?3 WHERE id = ?4",
+                params![project.name, project.color, project.icon, id],
+            )
+            .context("Falha ao atualizar projeto")?;
+
+        Ok(())
+    }
+
+    pub fn list_tags(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM tags ORDER BY name COLLATE NOCASE")
+            .context("Falha ao preparar consulta de tags")?;
+
+        let tags = stmt
+            .query_map([], |row| row.get(0))
+            .context("Falha ao listar tags")?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        Ok(tags)
+    }
+
+    // Retorna o id do projeto com este nome, criando-o se ainda não existir
+    fn resolve_project(&self, name: Option<&str>) -> Result<Option<i64>> {
+        let Some(name) = name.map(str::trim).filter(|name| !name.is_empty()) else {
+            return Ok(None);
+        };
+
+        self.conn
+            .execute(
+                "INSERT OR IGNORE INTO projects (name, color) VALUES (?1, ?2)",
+                params![name, Project::default_color(name)],
+            )
+            .context("Falha ao criar projeto")?;
+
+        let id = self
+            .conn
+            .query_row("SELECT id FROM projects WHERE name = ?1", [name], |row| {
+                row.get(0)
+            })
+            .context("Falha ao buscar projeto")?;
+
+        Ok(Some(id))
+    }
+
+    fn set_activity_tags(&self, activity_id: i64, tags: &[String]) -> Result<()> {
+        self.conn
+            .execute(
+                "DELETE FROM activity_tags WHERE activity_id = ?1",
+                [activity_id],
+            )
+            .context("Falha ao limpar tags da atividade")?;
+
+        for tag in tags {
+            self.conn
+                .execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [tag])
+                .context("Falha ao criar tag")?;
+            self.conn
+                .execute(
+                    "INSERT OR IGNORE INTO activity_tags (activity_id, tag_id)
+                     SELECT ?1, id FROM tags WHERE name = ?2",
+                    params![activity_id, tag],
+                )
+                .context("Falha ao associar tag à atividade")?;
+        }
+
+        Ok(())
+    }
+
     fn row_to_activity(&self, row: &Row) -> rusqlite::Result<Activity> {
         let timestamp_str: String = row.get(2)?;
-        let timestamp = DateTime::parse_from_rfc3339(&timestamp_str)
-            .map_err(|_e| {
-                rusqlite::Error::InvalidColumnType(
-                    2,
-                    "timestamp".to_string(),
-                    rusqlite::types::Type::Text,
-                )
-            })?
-            .with_timezone(&Local);
+        let timestamp = Self::parse_timestamp(&timestamp_str, 2, "timestamp")?;
+
+        let end_timestamp = match row.get::<_, Option<String>>(4)? {
+            Some(end_str) => Self::parse_timestamp(&end_str, 4, "end_timestamp")?,
+            None => timestamp,
+        };
 
         let date_str: String = row.get(3)?;
         let date = NaiveDate::parse_from_str(&date_str, "%Y-%m-%d").map_err(|_e| {
             rusqlite::Error::InvalidColumnType(3, "date".to_string(), rusqlite::types::Type::Text)
         })?;
 
+        let project = match row.get::<_, Option<i64>>(6)? {
+            Some(project_id) => Some(Project {
+                id: Some(project_id),
+                name: row.get(7)?,
+                color: row.get(8)?,
+                icon: row.get(9)?,
+            }),
+            None => None,
+        };
+
+        let tags = row
+            .get::<_, Option<String>>(10)?
+            .map(|tags| tags.split(',').map(str::to_string).collect())
+            .unwrap_or_default();
+
         Ok(Activity {
             id: Some(row.get(0)?),
             description: row.get(1)?,
             timestamp,
+            end_timestamp,
+            responses: row.get(5)?,
             date,
+            project,
+            tags,
+            kind: ActivityKind::parse(&row.get::<_, String>(11)?),
         })
     }
+
+    fn parse_timestamp(
+        value: &str,
+        index: usize,
+        column: &str,
+    ) -> rusqlite::Result<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|timestamp| timestamp.with_timezone(&Local))
+            .map_err(|_e| {
+                rusqlite::Error::InvalidColumnType(
+                    index,
+                    column.to_string(),
+                    rusqlite::types::Type::Text,
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+    use chrono::{Duration, FixedOffset, TimeZone};
+
+    // Atividade gravada com o horário escrito em outro fuso, como acontece com
+    // os registros de antes de uma mudança de horário de verão
+    fn insert_with_offset(
+        db: &Database,
+        description: &str,
+        at: DateTime<Local>,
+        offset_hours: i32,
+    ) {
+        let written = at.with_timezone(&FixedOffset::east_opt(offset_hours * 3600).unwrap());
+        db.conn
+            .execute(
+                "INSERT INTO activities (description, timestamp, end_timestamp, date)
+                 VALUES (?1, ?2, ?2, ?3)",
+                params![
+                    description,
+                    written.to_rfc3339(),
+                    at.date_naive().to_string()
+                ],
+            )
+            .unwrap();
+    }
+
+    fn noon() -> DateTime<Local> {
+        let today = Local::now().date_naive();
+        Local
+            .from_local_datetime(&today.and_hms_opt(12, 0, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn orders_activities_by_instant_across_offsets() {
+        let test = TestDb::new("database-order");
+        let noon = noon();
+        // O texto da primeira vem depois do da segunda
+        insert_with_offset(&test.db, "Email", noon, 12);
+        insert_with_offset(&test.db, "Leitura", noon + Duration::minutes(40), -12);
+
+        let day = test
+            .db
+            .get_activities_between(DateRange::day(noon.date_naive()))
+            .unwrap();
+        let descriptions: Vec<_> = day.iter().map(|a| a.description.as_str()).collect();
+        assert_eq!(descriptions, ["Email", "Leitura"]);
+        assert_eq!(
+            test.db
+                .get_last_activity_today()
+                .unwrap()
+                .unwrap()
+                .description,
+            "Leitura"
+        );
+    }
+
+    #[test]
+    fn inserting_in_the_past_splits_the_span_in_progress() {
+        let test = TestDb::new("database-split");
+        let noon = noon();
+        insert_with_offset(&test.db, "Email", noon, 12);
+        insert_with_offset(&test.db, "Leitura", noon + Duration::minutes(40), -12);
+
+        let at = noon + Duration::minutes(20);
+        let id = test
+            .db
+            .add_activity_at(&ActivityInput::parse("Reunião #time"), at)
+            .unwrap();
+
+        let day = test
+            .db
+            .get_activities_between(DateRange::day(noon.date_naive()))
+            .unwrap();
+        let spans: Vec<_> = day
+            .iter()
+            .map(|a| (a.description.as_str(), a.timestamp, a.end_timestamp))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("Email", noon, at),
+                ("Reunião", at, noon + Duration::minutes(40)),
+                (
+                    "Leitura",
+                    noon + Duration::minutes(40),
+                    noon + Duration::minutes(40)
+                ),
+            ]
+        );
+        assert_eq!(test.db.get_activity(id).unwrap().unwrap().tags, ["time"]);
+    }
+
+    fn local(date: &str, hour: u32, minute: u32) -> DateTime<Local> {
+        let date: NaiveDate = date.parse().unwrap();
+        Local
+            .from_local_datetime(&date.and_hms_opt(hour, minute, 0).unwrap())
+            .earliest()
+            .unwrap()
+    }
+
+    #[test]
+    fn update_and_delete_report_missing_activity() {
+        let test = TestDb::new("database-missing");
+        let at = local("2026-03-02", 9, 0);
+
+        let error = test
+            .db
+            .update_activity(42, &ActivityInput::parse("Email @cliente"), at, at)
+            .unwrap_err();
+        assert!(error.to_string().contains("42 não encontrada"));
+        // O projeto criado para a edição não fica para trás
+        assert!(test.db.list_projects().unwrap().is_empty());
+
+        let error = test.db.delete_activity(42).unwrap_err();
+        assert!(error.to_string().contains("42 não encontrada"));
+    }
+
+    #[test]
+    fn update_rejects_end_before_start() {
+        let test = TestDb::new("database-end-before-start");
+        let start = local("2026-03-02", 9, 0);
+        let id = test
+            .db
+            .add_activity_at(&ActivityInput::parse("Email"), start)
+            .unwrap();
+
+        let result = test.db.update_activity(
+            id,
+            &ActivityInput::parse("Leitura"),
+            start,
+            start - Duration::minutes(1),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(
+            test.db.get_activity(id).unwrap().unwrap().description,
+            "Email"
+        );
+    }
+
+    #[test]
+    fn update_moves_activity_to_the_day_it_starts() {
+        let test = TestDb::new("database-midnight");
+        let id = test
+            .db
+            .add_activity_at(&ActivityInput::parse("Deploy"), local("2026-03-03", 9, 0))
+            .unwrap();
+
+        let start = local("2026-03-02", 23, 30);
+        let end = local("2026-03-03", 0, 30);
+        test.db
+            .update_activity(id, &ActivityInput::parse("Deploy"), start, end)
+            .unwrap();
+
+        let activity = test.db.get_activity(id).unwrap().unwrap();
+        assert_eq!(activity.date.to_string(), "2026-03-02");
+        assert_eq!((activity.timestamp, activity.end_timestamp), (start, end));
+        let day = DateRange::day("2026-03-02".parse().unwrap());
+        assert_eq!(test.db.get_activities_between(day).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn update_replaces_tags_and_project() {
+        let test = TestDb::new("database-tags");
+        let at = local("2026-03-02", 9, 0);
+        let id = test
+            .db
+            .add_activity_at(
+                &ActivityInput::parse("Revisão #código #urgente @interno"),
+                at,
+            )
+            .unwrap();
+
+        test.db
+            .update_activity(
+                id,
+                &ActivityInput::parse("Revisão #código #pr @cliente"),
+                at,
+                at,
+            )
+            .unwrap();
+
+        let activity = test.db.get_activity(id).unwrap().unwrap();
+        let mut tags = activity.tags.clone();
+        tags.sort();
+        assert_eq!(tags, ["código", "pr"]);
+        assert_eq!(activity.project.unwrap().name, "cliente");
+    }
+
+    #[test]
+    fn editing_automatic_activity_counts_as_one_answer() {
+        let test = TestDb::new("database-edit-away");
+        let at = local("2026-03-02", 9, 0);
+        let id = test
+            .db
+            .add_unanswered_activity(&ActivityInput::parse("Ausente"), at, ActivityKind::Away)
+            .unwrap();
+        let day = DateRange::day(at.date_naive());
+
+        test.db
+            .update_activity(id, &ActivityInput::parse("Almoço"), at, at)
+            .unwrap();
+
+        let counts = test.db.response_counts(day).unwrap();
+        assert_eq!((counts.new_entries, counts.continuations), (1, 0));
+        assert_eq!(
+            test.db.get_activity(id).unwrap().unwrap().kind,
+            ActivityKind::Answered
+        );
+    }
+
+    #[test]
+    fn fts_query_quotes_every_term() {
+        let query = |text: &str| Database::build_fts_query(text);
+
+        assert_eq!(query("reunião cliente").unwrap(), "\"reunião\" \"cliente\"");
+        assert_eq!(query("rev*").unwrap(), "\"rev\"*");
+        assert_eq!(
+            query("\"code  review\" bug").unwrap(),
+            "\"code review\" \"bug\""
+        );
+        // Operadores do FTS5 viram palavras comuns
+        assert_eq!(
+            query("a OR b NEAR c").unwrap(),
+            "\"a\" \"OR\" \"b\" \"NEAR\" \"c\""
+        );
+        assert_eq!(query("-foo").unwrap(), "\"-foo\"");
+        // Aspas sem par: o resto do texto vira frase
+        assert_eq!(query("deploy \"sexta").unwrap(), "\"deploy\" \"sexta\"");
+        assert_eq!(query("(foo) ^bar:").unwrap(), "\"foo\" \"bar\"");
+
+        for empty in ["", "   ", "*", "\"", "\"\"", "( ) :"] {
+            assert_eq!(query(empty), None, "{empty:?}");
+        }
+    }
+
+    #[test]
+    fn search_accepts_any_partial_input() {
+        let test = TestDb::new("database-search-input");
+        test.db
+            .add_activity_at(
+                &ActivityInput::parse("Revisão do código #pr"),
+                local("2026-03-02", 9, 0),
+            )
+            .unwrap();
+
+        // O visualizador busca a cada tecla, então nada disto pode dar erro
+        for text in [
+            "\"",
+            "\"revis",
+            "*",
+            "-",
+            "- -",
+            "NEAR",
+            "NEAR(",
+            "OR",
+            "AND NOT",
+            "rev*",
+            "a*b",
+            "código\"",
+            "col:umn",
+            "^",
+            "{x}",
+            "\"\"\"",
+        ] {
+            assert!(test.db.search(text, 10).is_ok(), "{text:?}");
+        }
+
+        // Acentos são ignorados nos dois sentidos
+        for text in ["revisao", "REVISÃO", "codigo", "revi*"] {
+            assert_eq!(test.db.search(text, 10).unwrap().len(), 1, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn search_index_follows_edits_and_deletes() {
+        let test = TestDb::new("database-search-sync");
+        let at = local("2026-03-02", 9, 0);
+        let id = test
+            .db
+            .add_activity_at(&ActivityInput::parse("Planejamento #sprint @interno"), at)
+            .unwrap();
+        let found = |text: &str| test.db.search(text, 10).unwrap().len();
+        assert_eq!(
+            (found("planejamento"), found("sprint"), found("interno")),
+            (1, 1, 1)
+        );
+
+        test.db
+            .update_activity(
+                id,
+                &ActivityInput::parse("Retrospectiva #time @cliente"),
+                at,
+                at,
+            )
+            .unwrap();
+        assert_eq!(
+            (found("planejamento"), found("sprint"), found("interno")),
+            (0, 0, 0)
+        );
+        assert_eq!(
+            (found("retrospectiva"), found("time"), found("cliente")),
+            (1, 1, 1)
+        );
+
+        test.db.delete_activity(id).unwrap();
+        assert_eq!(
+            (found("retrospectiva"), found("time"), found("cliente")),
+            (0, 0, 0)
+        );
+    }
 }
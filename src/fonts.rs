@@ -5,7 +5,7 @@ const FIRA_CODE_NERD_REGULAR: &[u8] = include_bytes!("../assets/fonts/FiraCode-R
 const FIRA_CODE_NERD_BOLD: &[u8] = include_bytes!("../assets/fonts/FiraCode-Bold.ttf");
 
 pub fn setup_fonts(ctx: &Context) {
-    println!("Configurando fontes Nerd Font...");
+    log::debug!("Configurando fontes Nerd Font...");
     let mut fonts = FontDefinitions::default();
 
     // Adicionar FiraCode Nerd Font Regular
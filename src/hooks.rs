@@ -0,0 +1,335 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use log::{debug, warn};
+use serde::Serialize;
+use std::io::{Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::{AppConfig, HooksConfig};
+use crate::models::Activity;
+
+// Intervalo entre verificações do processo do hook
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// Depois que o comando termina, quanto esperar pelo resto da saída
+const OUTPUT_GRACE: Duration = Duration::from_millis(500);
+
+// Eventos que podem disparar um comando da seção [hooks] do config.toml
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    ActivitySaved,
+    ActivityContinued,
+    InquirySkipped,
+    DaemonStart,
+}
+
+impl HookEvent {
+    // Nome da chave no config.toml, também passado ao comando em ACV_EVENT
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::ActivitySaved => "on_activity_saved",
+            HookEvent::ActivityContinued => "on_activity_continued",
+            HookEvent::InquirySkipped => "on_inquiry_skipped",
+            HookEvent::DaemonStart => "on_daemon_start",
+        }
+    }
+}
+
+// O que o comando recebe na entrada padrão, em uma linha JSON
+#[derive(Serialize)]
+struct HookPayload<'a> {
+    event: &'static str,
+    at: DateTime<Local>,
+    pid: u32,
+    activity: Option<&'a Activity>,
+}
+
+// Os clones compartilham a lista de hooks em andamento de `run_in_background`
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    config: HooksConfig,
+    running: Arc<Mutex<Vec<thread::JoinHandle<()>>>>,
+}
+
+impl Hooks {
+    pub fn new(config: HooksConfig) -> Self {
+        Self {
+            config,
+            running: Arc::default(),
+        }
+    }
+
+    // Hooks da configuração atual. Uma configuração inválida não deve impedir o
+    // registro da atividade, então ela só desativa os hooks.
+    pub fn load() -> Self {
+        match AppConfig::load() {
+            Ok(config) => Self::new(config.hooks),
+            Err(e) => {
+                warn!("⚠️  Hooks desativados: {e:#}");
+                Self::default()
+            }
+        }
+    }
+
+    fn command(&self, event: HookEvent) -> &str {
+        let command = match event {
+            HookEvent::ActivitySaved => &self.config.on_activity_saved,
+            HookEvent::ActivityContinued => &self.config.on_activity_continued,
+            HookEvent::InquirySkipped => &self.config.on_inquiry_skipped,
+            HookEvent::DaemonStart => &self.config.on_daemon_start,
+        };
+        command.trim()
+    }
+
+    pub fn is_set(&self, event: HookEvent) -> bool {
+        !self.command(event).is_empty()
+    }
+
+    // Executa o comando do evento e espera ele terminar, até o tempo limite.
+    // Falhas do comando só são registradas no log.
+    pub fn run(&self, event: HookEvent, activity: Option<&Activity>) {
+        let command = self.command(event);
+        if command.is_empty() {
+            return;
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+        let payload = HookPayload {
+            event: event.name(),
+            at: Local::now(),
+            pid: std::process::id(),
+            activity,
+        };
+
+        debug!("🪝 Executando {}: {command}", event.name());
+        match execute(command, &payload, timeout) {
+            Ok(Some(status)) if status.success() => debug!("🪝 {} concluído", event.name()),
+            Ok(Some(status)) => warn!("⚠️  Hook {} terminou com {status}", event.name()),
+            Ok(None) => warn!(
+                "⚠️  Hook {} encerrado após {}s sem terminar",
+                event.name(),
+                timeout.as_secs()
+            ),
+            Err(e) => warn!("⚠️  Falha no hook {}: {e:#}", event.name()),
+        }
+    }
+
+    // Executa o hook numa thread, sem prender quem disparou o evento (ex.: a
+    // janela do inquérito ou a API). Antes de encerrar, o processo chama `wait`.
+    pub fn run_in_background(&self, event: HookEvent, activity: Option<Activity>) {
+        if !self.is_set(event) {
+            return;
+        }
+        let hooks = self.clone();
+        let handle = thread::spawn(move || hooks.run(event, activity.as_ref()));
+        if let Ok(mut running) = self.running.lock() {
+            // Processos longos (a API) não chegam a chamar `wait`
+            running.retain(|handle| !handle.is_finished());
+            running.push(handle);
+        }
+    }
+
+    // Espera os hooks disparados por `run_in_background` terminarem
+    pub fn wait(&self) {
+        let running = match self.running.lock() {
+            Ok(mut running) => std::mem::take(&mut *running),
+            Err(_) => return,
+        };
+        for handle in running {
+            let _ = handle.join();
+        }
+    }
+}
+
+pub fn shell(command: &str) -> Command {
+    if cfg!(target_os = "windows") {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+// Variáveis de ambiente com os mesmos dados do JSON, para comandos simples
+fn environment(payload: &HookPayload) -> Vec<(&'static str, String)> {
+    let mut env = vec![
+        ("ACV_EVENT", payload.event.to_string()),
+        ("ACV_PID", payload.pid.to_string()),
+    ];
+
+    if let Some(activity) = payload.activity {
+        env.extend([
+            (
+                "ACV_ACTIVITY_ID",
+                activity.id.map(|id| id.to_string()).unwrap_or_default(),
+            ),
+            ("ACV_ACTIVITY_DESCRIPTION", activity.description.clone()),
+            (
+                "ACV_ACTIVITY_PROJECT",
+                activity
+                    .project
+                    .as_ref()
+                    .map(|project| project.name.clone())
+                    .unwrap_or_default(),
+            ),
+            ("ACV_ACTIVITY_TAGS", activity.tags.join(",")),
+            ("ACV_ACTIVITY_START", activity.timestamp.to_rfc3339()),
+            ("ACV_ACTIVITY_END", activity.end_timestamp.to_rfc3339()),
+            ("ACV_ACTIVITY_KIND", activity.kind.as_str().to_string()),
+        ]);
+    }
+
+    env
+}
+
+// `None` quando o comando passou do tempo limite e foi encerrado
+fn execute(command: &str, payload: &HookPayload, timeout: Duration) -> Result<Option<ExitStatus>> {
+    let mut json = serde_json::to_string(payload).context("Falha ao serializar evento")?;
+    json.push('\n');
+
+    // A saída padrão do processo pode ser lida por outro programa (ex.: o
+    // resultado de `inquiry --report`), então a do hook vai só para o log
+    let mut shell = shell(command);
+    // Grupo de processos próprio, para que o tempo limite encerre também o que
+    // o comando iniciou (ex.: `curl` dentro de um script)
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut shell, 0);
+    let mut child = shell
+        .envs(environment(payload))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Falha ao executar \"{command}\""))?;
+
+    // Escrita e leitura em threads separadas: um comando que não lê a entrada
+    // ou escreve muito na saída não trava quem disparou o hook
+    if let Some(mut stdin) = child.stdin.take() {
+        thread::spawn(move || {
+            let _ = stdin.write_all(json.as_bytes());
+        });
+    }
+    let stdout = read_output(&mut child.stdout);
+    let stderr = read_output(&mut child.stderr);
+
+    let status = wait_until(&mut child, Instant::now() + timeout)?;
+
+    // Um processo deixado em segundo plano pelo comando (ex.: `timer &`) pode
+    // manter a saída aberta; nesse caso ela não é esperada
+    let deadline = Instant::now() + OUTPUT_GRACE;
+    for (name, output) in [("stdout", stdout), ("stderr", stderr)] {
+        let Some(reader) = output else {
+            continue;
+        };
+        while !reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        if reader.is_finished()
+            && let Ok(output) = reader.join()
+            && !output.trim().is_empty()
+        {
+            debug!("🪝 {name}: {}", output.trim_end());
+        }
+    }
+
+    Ok(status)
+}
+
+pub fn read_output<R: Read + Send + 'static>(
+    pipe: &mut Option<R>,
+) -> Option<thread::JoinHandle<String>> {
+    let mut pipe = pipe.take()?;
+    Some(thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = pipe.read_to_end(&mut output);
+        String::from_utf8_lossy(&output).into_owned()
+    }))
+}
+
+pub fn wait_until(child: &mut Child, deadline: Instant) -> Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait().context("Falha ao aguardar o comando")? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            kill_group(child);
+            let _ = child.wait();
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// Encerra o processo e, se ele lidera um grupo (ver `execute`), tudo o que ele
+// iniciou. O processo ainda não foi aguardado, então nenhum outro grupo tem o
+// mesmo número.
+fn kill_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(pid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: killpg só recebe números; um grupo inexistente devolve ESRCH
+        unsafe { libc::killpg(pid, libc::SIGKILL) };
+    }
+    let _ = child.kill();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    fn payload() -> HookPayload<'static> {
+        HookPayload {
+            event: HookEvent::ActivitySaved.name(),
+            at: Local::now(),
+            pid: std::process::id(),
+            activity: None,
+        }
+    }
+
+    // Vivo e ainda não aguardado (um zumbi já foi encerrado)
+    fn is_running(pid: &str) -> bool {
+        std::fs::read_to_string(format!("/proc/{pid}/stat"))
+            .is_ok_and(|stat| !stat.contains(") Z "))
+    }
+
+    #[test]
+    fn timeout_kills_processes_started_by_the_hook() {
+        let temp = TempDir::new("hooks-timeout");
+        let pid_file = temp.join("pid");
+        let command = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let started = Instant::now();
+        let status = execute(&command, &payload(), Duration::from_secs(1)).unwrap();
+
+        assert!(status.is_none());
+        // A saída não fica presa ao `sleep`, que a herdou
+        assert!(started.elapsed() < Duration::from_secs(5));
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let pid = pid.trim();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while is_running(pid) && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        assert!(!is_running(pid), "sleep {pid} continua em execução");
+    }
+
+    #[test]
+    fn hook_receives_event_in_stdin_and_environment() {
+        let temp = TempDir::new("hooks-payload");
+        let out = temp.join("out");
+        let command = format!("cat > {0}; echo \"$ACV_EVENT\" >> {0}", out.display());
+
+        let status = execute(&command, &payload(), Duration::from_secs(5)).unwrap();
+
+        assert!(status.unwrap().success());
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert!(written.starts_with("{\"event\":\"on_activity_saved\""));
+        assert!(written.ends_with("\non_activity_saved\n"));
+    }
+}
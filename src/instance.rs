@@ -0,0 +1,165 @@
+use anyhow::{Context, Result, bail};
+use std::fmt;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{ErrorKind, Read, Seek, Write};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::config::AppConfig;
+
+const DAEMON_LOCK: &str = "activity-inquirer-daemon.lock";
+const INQUIRY_LOCK: &str = "activity-inquirer-inquiry.lock";
+const INQUIRY_FOCUS: &str = "activity-inquirer-inquiry.focus";
+
+// Quanto esperar por uma trava que pode estar só sendo verificada por outro processo
+const PROBE_WAIT: Duration = Duration::from_millis(200);
+
+// Arquivos de execução (socket, travas) ficam no diretório de runtime do
+// usuário, ou junto da configuração quando o sistema não tem um
+pub fn runtime_file(name: &str) -> Result<PathBuf> {
+    let dir = match dirs::runtime_dir() {
+        Some(dir) => dir,
+        None => AppConfig::get_config_path()?
+            .parent()
+            .context("Caminho de configuração sem diretório")?
+            .to_path_buf(),
+    };
+
+    Ok(dir.join(name))
+}
+
+// Trava exclusiva sobre um arquivo com o PID do dono. O sistema libera a trava
+// quando o processo termina, mesmo que ele seja morto.
+pub struct InstanceLock {
+    _file: File,
+}
+
+// Processo que já tem a trava
+#[derive(Debug, Clone, Copy)]
+pub struct Holder(pub Option<u32>);
+
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(pid) => write!(f, "PID {pid}"),
+            None => write!(f, "PID desconhecido"),
+        }
+    }
+}
+
+enum LockAttempt {
+    Acquired(InstanceLock),
+    Held(Holder),
+}
+
+fn try_lock(name: &str) -> Result<LockAttempt> {
+    let path = runtime_file(name)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Falha ao criar diretório de execução: {parent:?}"))?;
+    }
+
+    // Sem truncar: o conteúdo é o PID de quem já tem a trava
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .with_context(|| format!("Falha ao abrir arquivo de trava: {path:?}"))?;
+
+    // Uma verificação (`probe`) segura a trava por um instante; nesse caso
+    // vale esperar um pouco antes de concluir que outro processo é o dono
+    let deadline = Instant::now() + PROBE_WAIT;
+    let mut attempt = file.try_lock();
+    while matches!(attempt, Err(TryLockError::WouldBlock)) && Instant::now() < deadline {
+        thread::sleep(PROBE_WAIT / 10);
+        attempt = file.try_lock();
+    }
+
+    match attempt {
+        Ok(()) => {
+            file.set_len(0)
+                .and_then(|_| file.rewind())
+                .and_then(|_| write!(file, "{}", std::process::id()))
+                .and_then(|_| file.flush())
+                .with_context(|| format!("Falha ao gravar PID em {path:?}"))?;
+            Ok(LockAttempt::Acquired(InstanceLock { _file: file }))
+        }
+        Err(TryLockError::WouldBlock) => Ok(LockAttempt::Held(read_holder(&mut file))),
+        Err(TryLockError::Error(e)) => {
+            Err(e).with_context(|| format!("Falha ao travar arquivo: {path:?}"))
+        }
+    }
+}
+
+// Quem tem a trava, sem tomá-la nem alterar o arquivo: uma trava compartilhada
+// só é negada enquanto outro processo tem a exclusiva, e é solta em seguida
+fn probe(name: &str) -> Result<Option<Holder>> {
+    let path = runtime_file(name)?;
+    let mut file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Falha ao abrir arquivo de trava: {path:?}"));
+        }
+    };
+
+    match file.try_lock_shared() {
+        Ok(()) => {
+            let _ = file.unlock();
+            Ok(None)
+        }
+        Err(TryLockError::WouldBlock) => Ok(Some(read_holder(&mut file))),
+        Err(TryLockError::Error(e)) => {
+            Err(e).with_context(|| format!("Falha ao verificar trava: {path:?}"))
+        }
+    }
+}
+
+// PID gravado pelo dono da trava
+fn read_holder(file: &mut File) -> Holder {
+    let mut content = String::new();
+    let _ = file.read_to_string(&mut content);
+    Holder(content.trim().parse().ok())
+}
+
+// Garante que só um daemon rode por usuário
+pub fn lock_daemon() -> Result<InstanceLock> {
+    match try_lock(DAEMON_LOCK)? {
+        LockAttempt::Acquired(lock) => Ok(lock),
+        LockAttempt::Held(holder) => bail!("Já existe um daemon em execução ({holder})"),
+    }
+}
+
+// Processo do daemon em execução, se houver
+pub fn running_daemon() -> Result<Option<Holder>> {
+    probe(DAEMON_LOCK)
+}
+
+// Trava da janela de inquérito, ou quem a mantém quando já existe uma aberta
+pub fn lock_inquiry() -> Result<std::result::Result<InstanceLock, Holder>> {
+    Ok(match try_lock(INQUIRY_LOCK)? {
+        LockAttempt::Acquired(lock) => Ok(lock),
+        LockAttempt::Held(holder) => Err(holder),
+    })
+}
+
+// Processo da janela de inquérito aberta, se houver
+pub fn open_inquiry() -> Result<Option<Holder>> {
+    probe(INQUIRY_LOCK)
+}
+
+// Pede para a janela de inquérito aberta vir para frente. A janela observa a
+// data de modificação deste arquivo.
+pub fn request_inquiry_focus() -> Result<()> {
+    let path = runtime_file(INQUIRY_FOCUS)?;
+    fs::write(&path, std::process::id().to_string())
+        .with_context(|| format!("Falha ao sinalizar janela de inquérito: {path:?}"))
+}
+
+pub fn inquiry_focus_requested_at() -> Option<SystemTime> {
+    let path = runtime_file(INQUIRY_FOCUS).ok()?;
+    fs::metadata(path).ok()?.modified().ok()
+}
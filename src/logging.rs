@@ -0,0 +1,305 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use env_filter::Filter;
+use log::{Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// Rotação do arquivo de log: ao passar do limite, `acv-inq.log` vira
+// `acv-inq.log.1`, o `.1` vira `.2` e assim por diante, até `LOG_BACKUPS`
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+const LOG_BACKUPS: u32 = 3;
+
+// O arquivo sempre guarda as mensagens de depuração do programa, para
+// investigar problemas do daemon depois que eles acontecem
+const FILE_FILTER: &str = "warn,acv_inq=debug";
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Falha ao criar diretório de logs: {parent:?}"))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Falha ao abrir arquivo de log: {path:?}"))?;
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.size + line.len() as u64 > MAX_LOG_SIZE {
+            self.rotate();
+        }
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.size += line.len() as u64;
+        }
+    }
+
+    fn rotate(&mut self) {
+        for index in (1..LOG_BACKUPS).rev() {
+            let _ = fs::rename(
+                backup_path(&self.path, index),
+                backup_path(&self.path, index + 1),
+            );
+        }
+        let _ = fs::rename(&self.path, backup_path(&self.path, 1));
+
+        // Outro processo (ex.: a janela de inquérito) pode já ter rotacionado;
+        // em qualquer caso, continua no arquivo que está no caminho principal
+        if let Ok(reopened) = Self::open(self.path.clone()) {
+            *self = reopened;
+        }
+    }
+}
+
+fn backup_path(path: &Path, index: u32) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{index}"));
+    PathBuf::from(name)
+}
+
+struct Logger {
+    console: Filter,
+    file_filter: Filter,
+    file: Option<Mutex<LogFile>>,
+    verbose: bool,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.console.enabled(metadata)
+            || (self.file.is_some() && self.file_filter.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.console.matches(record) {
+            let line = if self.verbose {
+                format!(
+                    "{} {:<5} {}: {}",
+                    Local::now().format("%H:%M:%S"),
+                    record.level(),
+                    record.target(),
+                    record.args()
+                )
+            } else if record.target().starts_with("acv_inq") {
+                // As mensagens do programa já trazem o próprio ícone
+                record.args().to_string()
+            } else {
+                format!("[{} {}] {}", record.level(), record.target(), record.args())
+            };
+            eprintln!("{line}");
+        }
+
+        if let Some(file) = &self.file
+            && (self.file_filter.matches(record) || self.console.matches(record))
+        {
+            let line = format!(
+                "{} {:<5} [{}] {}: {}\n",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                record.level(),
+                std::process::id(),
+                record.target(),
+                record.args()
+            );
+            if let Ok(mut file) = file.lock() {
+                file.write_line(&line);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file
+            && let Ok(mut file) = file.lock()
+        {
+            let _ = file.file.flush();
+        }
+    }
+}
+
+// Arquivo de log no diretório de dados do usuário
+// (Linux: ~/.local/share/activity-inquirer/logs/acv-inq.log)
+pub fn log_path() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .context("Não foi possível encontrar o diretório de dados do usuário")?;
+
+    Ok(data_dir
+        .join("activity-inquirer")
+        .join("logs")
+        .join("acv-inq.log"))
+}
+
+// Filtro do terminal: `RUST_LOG` quando definido (ex.: `RUST_LOG=acv_inq::daemon=trace`),
+// senão o nível escolhido com `-v`
+fn console_filter(verbose: u8) -> Filter {
+    console_filter_from(verbose, std::env::var("RUST_LOG").ok())
+}
+
+fn console_filter_from(verbose: u8, rust_log: Option<String>) -> Filter {
+    let spec = match rust_log {
+        Some(spec) if !spec.trim().is_empty() && verbose == 0 => spec,
+        _ => match verbose {
+            0 => "warn,acv_inq=info".to_string(),
+            1 => "info,acv_inq=debug".to_string(),
+            _ => "debug,acv_inq=trace".to_string(),
+        },
+    };
+
+    env_filter::Builder::new().parse(&spec).build()
+}
+
+pub fn init(verbose: u8) {
+    let console = console_filter(verbose);
+    let file_filter = env_filter::Builder::new().parse(FILE_FILTER).build();
+
+    let (file, file_error) = match log_path().and_then(LogFile::open) {
+        Ok(file) => (Some(Mutex::new(file)), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let max_level = if file.is_some() {
+        console.filter().max(file_filter.filter())
+    } else {
+        console.filter()
+    };
+
+    let logger = Logger {
+        console,
+        file_filter,
+        file,
+        verbose: verbose > 0,
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(max_level);
+    }
+
+    // Pânicos (ex.: falha ao abrir a janela) também vão para o arquivo. O
+    // backtrace só é capturado com RUST_BACKTRACE, como no tratamento padrão.
+    std::panic::set_hook(Box::new(|info| {
+        let backtrace = std::backtrace::Backtrace::capture();
+        match backtrace.status() {
+            std::backtrace::BacktraceStatus::Captured => log::error!("💥 {info}\n{backtrace}"),
+            _ => log::error!("💥 {info}"),
+        }
+    }));
+
+    if let Some(e) = file_error {
+        log::warn!("⚠️  Log apenas no terminal: {e:#}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use log::Level;
+
+    fn enabled(filter: &Filter, level: Level, target: &str) -> bool {
+        filter.enabled(&Metadata::builder().level(level).target(target).build())
+    }
+
+    #[test]
+    fn file_keeps_program_debug_messages() {
+        let filter = env_filter::Builder::new().parse(FILE_FILTER).build();
+
+        assert!(enabled(&filter, Level::Debug, "acv_inq::daemon"));
+        assert!(!enabled(&filter, Level::Trace, "acv_inq::daemon"));
+        assert!(enabled(&filter, Level::Warn, "eframe"));
+        assert!(!enabled(&filter, Level::Info, "eframe"));
+    }
+
+    #[test]
+    fn console_level_follows_verbosity() {
+        let quiet = console_filter_from(0, None);
+        assert!(enabled(&quiet, Level::Info, "acv_inq::daemon"));
+        assert!(!enabled(&quiet, Level::Debug, "acv_inq::daemon"));
+        assert!(!enabled(&quiet, Level::Info, "eframe"));
+
+        let verbose = console_filter_from(1, None);
+        assert!(enabled(&verbose, Level::Debug, "acv_inq::daemon"));
+        assert!(enabled(&verbose, Level::Info, "eframe"));
+
+        let very_verbose = console_filter_from(2, None);
+        assert!(enabled(&very_verbose, Level::Trace, "acv_inq::daemon"));
+        assert!(!enabled(&very_verbose, Level::Trace, "eframe"));
+    }
+
+    #[test]
+    fn rust_log_selects_levels_per_target() {
+        let spec = Some("warn,acv_inq::daemon=trace".to_string());
+
+        let filter = console_filter_from(0, spec.clone());
+        assert!(enabled(&filter, Level::Trace, "acv_inq::daemon"));
+        assert!(!enabled(&filter, Level::Info, "acv_inq::cli"));
+        assert!(enabled(&filter, Level::Warn, "acv_inq::cli"));
+
+        // `-v` tem precedência sobre RUST_LOG, e RUST_LOG vazio é ignorado
+        let filter = console_filter_from(1, spec);
+        assert!(!enabled(&filter, Level::Trace, "acv_inq::daemon"));
+        assert!(enabled(&filter, Level::Debug, "acv_inq::cli"));
+
+        let filter = console_filter_from(0, Some(" ".to_string()));
+        assert!(enabled(&filter, Level::Info, "acv_inq::cli"));
+    }
+
+    #[test]
+    fn rotation_keeps_only_the_last_backups() {
+        let temp = TempDir::new("logging-rotation");
+        let path = temp.join("logs").join("acv-inq.log");
+        let mut log = LogFile::open(path.clone()).unwrap();
+
+        // Cada geração enche exatamente o arquivo; a primeira linha da seguinte
+        // provoca a rotação
+        let line_size = 64 * 1024;
+        for generation in 0..=LOG_BACKUPS + 1 {
+            let line = format!("{generation}{}\n", "x".repeat(line_size - 2));
+            for _ in 0..MAX_LOG_SIZE / line_size as u64 {
+                log.write_line(&line);
+            }
+        }
+        log.file.flush().unwrap();
+
+        let first_char = |path: &Path| {
+            let contents = fs::read_to_string(path).unwrap();
+            assert_eq!(contents.len() as u64, MAX_LOG_SIZE, "{path:?}");
+            contents.chars().next().unwrap()
+        };
+        assert_eq!(first_char(&path), '4');
+        assert_eq!(first_char(&backup_path(&path, 1)), '3');
+        assert_eq!(first_char(&backup_path(&path, 2)), '2');
+        assert_eq!(first_char(&backup_path(&path, 3)), '1');
+        // A geração mais antiga foi descartada
+        assert!(!backup_path(&path, 4).exists());
+    }
+
+    #[test]
+    fn reopening_continues_counting_existing_size() {
+        let temp = TempDir::new("logging-reopen");
+        let path = temp.join("acv-inq.log");
+        let line = format!("{}\n", "x".repeat(1023));
+
+        let mut log = LogFile::open(path.clone()).unwrap();
+        for _ in 0..MAX_LOG_SIZE / 1024 {
+            log.write_line(&line);
+        }
+        drop(log);
+
+        // Outro processo abre o arquivo já cheio e rotaciona na primeira linha
+        let mut log = LogFile::open(path.clone()).unwrap();
+        assert_eq!(log.size, MAX_LOG_SIZE);
+        log.write_line("nova\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nova\n");
+        assert!(backup_path(&path, 1).exists());
+    }
+}
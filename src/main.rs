@@ -1,83 +1,336 @@
+mod cli;
 mod config;
+mod control;
 mod daemon;
 mod database;
 mod fonts;
+mod hooks;
+mod instance;
+mod logging;
+mod migrations;
 mod models;
+mod options;
+mod prompter;
+mod scheduler;
+mod server;
+mod service;
+#[cfg(test)]
+mod test_support;
 mod ui;
+mod webhooks;
 
 use anyhow::Result;
-use clap::{Arg, Command};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use eframe::egui;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(
+    name = "acv-inq",
+    about = "Activity Inquirer - Rastreador de atividades pessoais",
+    version
+)]
+struct Cli {
+    /// Arquivo de configuração a usar no lugar do padrão
+    #[arg(long, global = true, value_name = "ARQUIVO")]
+    config: Option<PathBuf>,
+
+    /// Banco de dados SQLite a usar no lugar do padrão
+    #[arg(long, global = true, value_name = "ARQUIVO")]
+    db: Option<PathBuf>,
+
+    /// Mostrar mais detalhes (repita para aumentar: -vv)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    // Flags antigas, mantidas para atalhos e entradas de autostart já instalados
+    #[arg(long, hide = true, conflicts_with_all = ["daemon", "settings"])]
+    inquiry: bool,
+    #[arg(long, hide = true, conflicts_with = "settings")]
+    daemon: bool,
+    #[arg(long, hide = true)]
+    settings: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Visualizador de atividades (padrão)
+    Viewer,
+    /// Modo de inquérito - pergunta sobre atividade atual
+    Inquiry(InquiryArgs),
+    /// Modo daemon - executa inquéritos automaticamente no intervalo configurado
+    Daemon(DaemonArgs),
+    /// Abrir tela de configurações
+    Settings,
+    /// Registrar uma atividade pelo terminal, sem abrir a interface
+    Log(LogArgs),
+    /// Mostrar as atividades de um dia
+    Show(ShowArgs),
+    /// Listar as atividades de um intervalo de datas (padrão: semana atual)
+    List(ListArgs),
+    /// Gerenciar a inicialização automática do daemon (serviço systemd de usuário)
+    Service(ServiceArgs),
+    /// Ver e forçar a entrega dos webhooks pendentes
+    Webhooks(WebhooksArgs),
+    /// Servir as atividades numa API HTTP local (JSON), para plugins e scripts
+    Serve(ServeArgs),
+}
+
+#[derive(Args, Default)]
+struct InquiryArgs {
+    /// Modo de agendamento que disparou o inquérito (preenchido pelo daemon)
+    #[arg(long, value_name = "MODO", hide = true)]
+    schedule_mode: Option<String>,
+
+    /// Horário planejado do inquérito, em RFC 3339 (preenchido pelo daemon)
+    #[arg(long, value_name = "HORÁRIO", hide = true)]
+    planned_at: Option<String>,
+
+    /// Perguntar o que aconteceu num período sem respostas (ex.: após suspensão)
+    #[arg(long)]
+    backfill: bool,
+
+    /// Início do período do --backfill (padrão: fim da última atividade)
+    #[arg(long, value_name = "HORÁRIO", requires = "backfill")]
+    since: Option<String>,
+
+    /// Imprimir o resultado como uma linha JSON na saída padrão (usado pelo daemon)
+    #[arg(long)]
+    report: bool,
+
+    /// Como perguntar (padrão: [prompter] kind do config.toml)
+    #[arg(long, value_enum)]
+    prompter: Option<config::PrompterKind>,
+
+    /// Aberto pelo daemon, que executa os hooks e webhooks depois que o inquérito termina
+    #[arg(long, hide = true)]
+    from_daemon: bool,
+}
+
+#[derive(Args)]
+struct ServiceArgs {
+    #[command(subcommand)]
+    action: service::ServiceAction,
+}
+
+#[derive(Args)]
+struct WebhooksArgs {
+    #[command(subcommand)]
+    action: webhooks::WebhookAction,
+}
+
+#[derive(Args)]
+struct ServeArgs {
+    /// Porta em 127.0.0.1 (padrão: [server] port do config.toml)
+    #[arg(long, short)]
+    port: Option<u16>,
+
+    /// Token exigido no cabeçalho Authorization: Bearer (padrão: [server] token do config.toml)
+    #[arg(long, value_name = "TOKEN")]
+    token: Option<String>,
+}
+
+#[derive(Args)]
+struct DaemonArgs {
+    /// Ação a enviar ao daemon em execução (sem ação, inicia o daemon)
+    #[command(subcommand)]
+    action: Option<control::ControlRequest>,
+}
+
+#[derive(Args)]
+struct LogArgs {
+    /// Descrição da atividade (aceita #tag e @projeto)
+    description: String,
+
+    /// Início da atividade: HH:MM, AAAA-MM-DD HH:MM ou RFC 3339
+    #[arg(long, value_name = "HORÁRIO")]
+    at: Option<String>,
+
+    /// Projeto ou categoria da atividade
+    #[arg(long, short)]
+    project: Option<String>,
+
+    /// Tags separadas por vírgula
+    #[arg(long, short)]
+    tags: Option<String>,
+
+    /// Imprimir a atividade registrada em JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct ShowArgs {
+    /// AAAA-MM-DD, hoje ou ontem (padrão: hoje)
+    #[arg(value_name = "DATA")]
+    date: Option<String>,
+
+    /// Formato de saída
+    #[arg(long, short, value_enum, default_value = "table")]
+    format: cli::OutputFormat,
+}
+
+#[derive(Args)]
+struct ListArgs {
+    /// Data inicial (AAAA-MM-DD)
+    #[arg(long, value_name = "DATA")]
+    from: Option<String>,
+
+    /// Data final (AAAA-MM-DD); exige --from
+    #[arg(long, value_name = "DATA", requires = "from")]
+    to: Option<String>,
+
+    /// Semana ISO, ex.: 2024-W05
+    #[arg(long, value_name = "AAAA-Www", conflicts_with_all = ["from", "to", "month"])]
+    week: Option<String>,
+
+    /// Mês, ex.: 2024-02
+    #[arg(long, value_name = "AAAA-MM", conflicts_with_all = ["from", "to"])]
+    month: Option<String>,
+
+    /// Formato de saída
+    #[arg(long, short, value_enum, default_value = "table")]
+    format: cli::OutputFormat,
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let matches = Command::new("acv-inq")
-        .about("Activity Inquirer - Rastreador de atividades pessoais")
-        .version("1.0.0")
-        .arg(
-            Arg::new("inquiry")
-                .long("inquiry")
-                .help("Modo de inquérito - pergunta sobre atividade atual")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            Arg::new("daemon")
-                .long("daemon")
-                .help("Modo daemon - executa inquéritos automaticamente a cada hora")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .arg(
-            Arg::new("settings")
-                .long("settings")
-                .help("Abrir tela de configurações")
-                .action(clap::ArgAction::SetTrue),
-        )
-        .get_matches();
-
-    let is_inquiry_mode = matches.get_flag("inquiry");
-    let is_daemon_mode = matches.get_flag("daemon");
-    let is_settings_mode = matches.get_flag("settings");
-
-    if is_daemon_mode {
-        run_daemon_mode().await
-    } else if is_inquiry_mode {
-        run_inquiry_mode()
-    } else if is_settings_mode {
-        run_settings_mode()
+    let cli = Cli::parse();
+
+    let legacy_command = if cli.inquiry {
+        Some(Commands::Inquiry(InquiryArgs::default()))
+    } else if cli.daemon {
+        Some(Commands::Daemon(DaemonArgs { action: None }))
+    } else if cli.settings {
+        Some(Commands::Settings)
     } else {
-        run_viewer_mode()
+        None
+    };
+
+    let command = match (legacy_command, cli.command) {
+        (Some(_), Some(_)) => Cli::command()
+            .error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "as flags --inquiry, --daemon e --settings não podem ser combinadas com subcomandos",
+            )
+            .exit(),
+        (legacy, command) => legacy.or(command).unwrap_or(Commands::Viewer),
+    };
+
+    options::init(options::GlobalOptions {
+        config: cli.config,
+        db: cli.db,
+        verbose: cli.verbose,
+    });
+
+    logging::init(options::get().verbose);
+    log::debug!("Configuração: {:?}", config::AppConfig::get_config_path()?);
+    log::debug!("Banco de dados: {:?}", database::Database::get_db_path()?);
+    if let Ok(path) = logging::log_path() {
+        log::debug!("Log: {path:?}");
+    }
+
+    match command {
+        Commands::Viewer => run_viewer_mode(),
+        Commands::Inquiry(args) => run_inquiry_mode(args),
+        Commands::Daemon(DaemonArgs {
+            action: Some(action),
+        }) => control::run_action(action),
+        Commands::Daemon(DaemonArgs { action: None }) => run_daemon_mode().await,
+        Commands::Settings => run_settings_mode(),
+        Commands::Log(args) => cli::run_log(
+            &args.description,
+            args.at.as_deref(),
+            args.project.as_deref(),
+            args.tags.as_deref(),
+            args.json,
+        ),
+        Commands::Show(args) => cli::run_show(args.date.as_deref(), args.format),
+        Commands::List(args) => cli::run_list(
+            args.from.as_deref(),
+            args.to.as_deref(),
+            args.week.as_deref(),
+            args.month.as_deref(),
+            args.format,
+        ),
+        Commands::Service(args) => service::run(args.action),
+        Commands::Webhooks(args) => webhooks::run_action(args.action),
+        Commands::Serve(args) => server::run(args.port, args.token),
     }
 }
 
-fn run_inquiry_mode() -> Result<()> {
-    let app = ui::InquiryApp::new()?;
+fn run_inquiry_mode(args: InquiryArgs) -> Result<()> {
+    let report = prompter::ReportSink::new(args.report);
 
-    let options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size([400.0, 300.0])
-            .with_title("Activity Inquirer")
-            .with_resizable(false)
-            .with_close_button(true),
-        // .with_always_on_top(),
-        ..Default::default()
+    // Só uma janela de inquérito por vez: uma segunda chamada traz a janela
+    // aberta para frente em vez de abrir outra
+    let _lock = match instance::lock_inquiry()? {
+        Ok(lock) => lock,
+        Err(holder) => {
+            instance::request_inquiry_focus()?;
+            log::info!(
+                "💬 Já existe uma janela de inquérito aberta ({holder}); trazendo-a para frente"
+            );
+            // Aberto pelo daemon, quem executa o hook é o daemon (ver `defer_notifications`)
+            if !args.from_daemon {
+                hooks::Hooks::load().run(hooks::HookEvent::InquirySkipped, None);
+            }
+            report.set(models::InquiryOutcome::Skipped, Vec::new());
+            std::process::exit(report.finish());
+        }
     };
 
-    let result = eframe::run_native(
-        "Activity Inquirer",
-        options,
-        Box::new(|cc| {
-            ui::theme::apply_theme(&cc.egui_ctx, models::Theme::default());
-            Box::new(app)
-        }),
-    );
-
-    // Garantir que a aplicação sempre retorne, mesmo em caso de erro
-    match result {
-        Ok(_) => Ok(()),
-        Err(e) => {
-            eprintln!("Aviso: Erro ao fechar aplicação: {e}");
-            Ok(()) // Não falhar o daemon por problemas de fechamento
-        }
+    let context = match args.schedule_mode {
+        Some(schedule_mode) => models::PromptContext {
+            schedule_mode,
+            planned_at: args
+                .planned_at
+                .as_deref()
+                .map(cli::parse_time)
+                .transpose()?,
+        },
+        None if args.backfill => models::PromptContext::backfill(),
+        None => models::PromptContext::manual(),
+    };
+    let mut session = prompter::InquirySession::new(context, report.clone())?;
+    if args.from_daemon {
+        session.defer_notifications();
+    }
+    let hooks = session.hooks().clone();
+    let backfill_start = if args.backfill {
+        let since = args.since.as_deref().map(cli::parse_time).transpose()?;
+        session.backfill_start(since)?
+    } else {
+        None
+    };
+
+    let config = config::AppConfig::load()?;
+    let kind = args.prompter.unwrap_or(config.prompter.kind);
+    log::debug!("Perguntando por: {}", kind.as_str());
+
+    // Um erro depois da resposta não invalida o que já foi salvo
+    if let Err(e) = prompter::create(kind, &config).run(session, backfill_start) {
+        log::warn!("⚠️  {e:#}");
+        report.fail_if_unanswered();
+    }
+
+    // Com a janela já fechada, a entrega não atrasa a resposta do usuário
+    if !args.from_daemon
+        && let Err(e) = database::Database::new().and_then(|db| {
+            webhooks::Webhooks::new(config.webhooks.clone()).deliver_pending(&db, false)
+        })
+    {
+        log::warn!("⚠️  {e:#}");
+    }
+    hooks.wait();
+
+    // O código de saída diz ao daemon (ou a um script) como o inquérito terminou
+    match report.finish() {
+        0 => Ok(()),
+        code => std::process::exit(code),
     }
 }
 
@@ -132,7 +385,8 @@ fn run_settings_mode() -> Result<()> {
 }
 
 async fn run_daemon_mode() -> Result<()> {
-    daemon::print_daemon_info();
+    // Mantida até o daemon terminar; o sistema a libera se o processo morrer
+    let _lock = instance::lock_daemon()?;
 
     daemon::run_daemon().await
 }
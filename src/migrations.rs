@@ -0,0 +1,480 @@
+use anyhow::{Context, Result, bail};
+use chrono::Local;
+use rusqlite::{Connection, Transaction};
+use std::path::{Path, PathBuf};
+
+// Uma migração leva o banco da versão `version - 1` para `version`.
+// As migrações são aplicadas em ordem, cada uma dentro da sua própria transação,
+// e a versão atual fica registrada em `PRAGMA user_version`.
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Transaction) -> Result<()>,
+}
+
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "tabela inicial de atividades",
+        apply: create_activities_table,
+    },
+    Migration {
+        version: 2,
+        description: "atividades como intervalos de tempo",
+        apply: convert_samples_to_spans,
+    },
+    Migration {
+        version: 3,
+        description: "projetos e tags",
+        apply: create_projects_and_tags,
+    },
+    Migration {
+        version: 4,
+        description: "índice de busca textual (FTS5)",
+        apply: create_search_index,
+    },
+    Migration {
+        version: 5,
+        description: "origem das respostas aos inquéritos",
+        apply: create_prompt_responses,
+    },
+    Migration {
+        version: 6,
+        description: "resultado de cada inquérito do daemon",
+        apply: create_prompt_outcomes,
+    },
+    Migration {
+        version: 7,
+        description: "atividades registradas automaticamente",
+        apply: add_activity_kind,
+    },
+    Migration {
+        version: 8,
+        description: "fila de entrega de webhooks",
+        apply: create_webhook_outbox,
+    },
+    Migration {
+        version: 9,
+        description: "reserva das entregas de webhooks",
+        apply: add_webhook_lease,
+    },
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.last().map(|m| m.version).unwrap_or(0)
+}
+
+pub fn current_version(conn: &Connection) -> Result<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+        .context("Falha ao ler versão do esquema do banco de dados")
+}
+
+// Aplica todas as migrações pendentes. Se o arquivo já tiver dados, uma cópia
+// de segurança é feita antes de qualquer alteração.
+pub fn run(conn: &mut Connection, db_path: &Path) -> Result<()> {
+    let current = current_version(conn)?;
+    let latest = latest_version();
+
+    if current > latest {
+        bail!(
+            "O banco de dados {db_path:?} está na versão {current} do esquema, mas este executável \
+             só conhece até a versão {latest}. Atualize o Activity Inquirer antes de continuar."
+        );
+    }
+
+    if current == latest {
+        return Ok(());
+    }
+
+    if has_user_data(conn)? {
+        let backup_path = backup(conn, db_path, current)?;
+        log::info!("💾 Backup do banco de dados criado em: {backup_path:?}");
+    }
+
+    for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+        let tx = conn
+            .transaction()
+            .context("Falha ao iniciar transação de migração")?;
+
+        (migration.apply)(&tx).with_context(|| {
+            format!(
+                "Falha ao aplicar migração {} ({})",
+                migration.version, migration.description
+            )
+        })?;
+
+        tx.pragma_update(None, "user_version", migration.version)
+            .context("Falha ao atualizar versão do esquema")?;
+        tx.commit()
+            .with_context(|| format!("Falha ao confirmar migração {}", migration.version))?;
+    }
+
+    Ok(())
+}
+
+fn has_user_data(conn: &Connection) -> Result<bool> {
+    let tables: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+            [],
+            |row| row.get(0),
+        )
+        .context("Falha ao inspecionar tabelas do banco de dados")?;
+
+    Ok(tables > 0)
+}
+
+fn backup(conn: &Connection, db_path: &Path, version: u32) -> Result<PathBuf> {
+    let file_name = db_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "activities.db".to_string());
+    let backup_path = db_path.with_file_name(format!(
+        "{file_name}.v{version}-{}.bak",
+        Local::now().format("%Y%m%d%H%M%S")
+    ));
+
+    conn.execute(
+        "VACUUM INTO ?1",
+        [backup_path.to_string_lossy().into_owned()],
+    )
+    .with_context(|| format!("Falha ao criar backup do banco de dados em {backup_path:?}"))?;
+
+    Ok(backup_path)
+}
+
+fn create_activities_table(tx: &Transaction) -> Result<()> {
+    // Bancos criados antes das migrações já possuem esta tabela com versão 0
+    tx.execute(
+        "CREATE TABLE IF NOT EXISTS activities (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            description TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            date TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn convert_samples_to_spans(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "ALTER TABLE activities ADD COLUMN end_timestamp TEXT;
+         ALTER TABLE activities ADD COLUMN responses INTEGER NOT NULL DEFAULT 1;",
+    )?;
+
+    // Cada registro antigo era uma amostra pontual. Amostras consecutivas com a
+    // mesma descrição no mesmo dia viram um único intervalo, que termina quando
+    // a próxima atividade do dia começa (ou na última amostra, no fim do dia).
+    let samples: Vec<(i64, String, String, String)> = {
+        let mut stmt = tx.prepare(
+            "SELECT id, description, timestamp, date FROM activities
+             ORDER BY date, unixepoch(timestamp, 'subsec'), id",
+        )?;
+        stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<rusqlite::Result<_>>()?
+    };
+
+    struct Span {
+        id: i64,
+        description: String,
+        date: String,
+        end: String,
+        responses: i64,
+        merged: Vec<i64>,
+    }
+
+    let mut spans: Vec<Span> = Vec::new();
+    for (id, description, timestamp, date) in samples {
+        match spans.last_mut() {
+            Some(span) if span.date == date && span.description == description => {
+                span.end = timestamp;
+                span.responses += 1;
+                span.merged.push(id);
+            }
+            previous => {
+                if let Some(span) = previous
+                    && span.date == date
+                {
+                    span.end = timestamp.clone();
+                }
+                spans.push(Span {
+                    id,
+                    description,
+                    date,
+                    end: timestamp,
+                    responses: 1,
+                    merged: Vec::new(),
+                });
+            }
+        }
+    }
+
+    for span in spans {
+        tx.execute(
+            "UPDATE activities SET end_timestamp = ?1, responses = ?2 WHERE id = ?3",
+            rusqlite::params![span.end, span.responses, span.id],
+        )?;
+        for id in span.merged {
+            tx.execute("DELETE FROM activities WHERE id = ?1", [id])?;
+        }
+    }
+
+    Ok(())
+}
+
+fn create_projects_and_tags(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE projects (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT NOT NULL,
+            icon TEXT
+        );
+        ALTER TABLE activities ADD COLUMN project_id INTEGER
+            REFERENCES projects(id) ON DELETE SET NULL;
+        CREATE TABLE tags (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE
+        );
+        CREATE TABLE activity_tags (
+            activity_id INTEGER NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (activity_id, tag_id)
+        );
+        CREATE INDEX idx_activity_tags_tag ON activity_tags(tag_id);",
+    )?;
+
+    Ok(())
+}
+
+// O índice guarda descrição, projeto e tags de cada atividade, com o mesmo
+// rowid da tabela `activities`, e é mantido em dia por gatilhos.
+fn create_search_index(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE VIRTUAL TABLE activities_fts USING fts5(
+            description, project, tags,
+            tokenize = 'unicode61 remove_diacritics 2'
+        );
+
+        INSERT INTO activities_fts (rowid, description, project, tags)
+        SELECT a.id, a.description, coalesce(p.name, ''),
+            coalesce((SELECT group_concat(t.name, ' ') FROM activity_tags at
+                JOIN tags t ON t.id = at.tag_id WHERE at.activity_id = a.id), '')
+        FROM activities a LEFT JOIN projects p ON p.id = a.project_id;
+
+        CREATE TRIGGER activities_fts_insert AFTER INSERT ON activities BEGIN
+            INSERT INTO activities_fts (rowid, description, project, tags)
+            VALUES (new.id, new.description,
+                coalesce((SELECT name FROM projects WHERE id = new.project_id), ''), '');
+        END;
+
+        CREATE TRIGGER activities_fts_update AFTER UPDATE OF description, project_id ON activities
+        BEGIN
+            UPDATE activities_fts SET description = new.description,
+                project = coalesce((SELECT name FROM projects WHERE id = new.project_id), '')
+            WHERE rowid = new.id;
+        END;
+
+        CREATE TRIGGER activities_fts_delete AFTER DELETE ON activities BEGIN
+            DELETE FROM activities_fts WHERE rowid = old.id;
+        END;
+
+        CREATE TRIGGER activity_tags_fts_insert AFTER INSERT ON activity_tags BEGIN
+            UPDATE activities_fts SET tags = coalesce((SELECT group_concat(t.name, ' ')
+                FROM activity_tags at JOIN tags t ON t.id = at.tag_id
+                WHERE at.activity_id = new.activity_id), '')
+            WHERE rowid = new.activity_id;
+        END;
+
+        CREATE TRIGGER activity_tags_fts_delete AFTER DELETE ON activity_tags BEGIN
+            UPDATE activities_fts SET tags = coalesce((SELECT group_concat(t.name, ' ')
+                FROM activity_tags at JOIN tags t ON t.id = at.tag_id
+                WHERE at.activity_id = old.activity_id), '')
+            WHERE rowid = old.activity_id;
+        END;
+
+        CREATE TRIGGER projects_fts_rename AFTER UPDATE OF name ON projects BEGIN
+            UPDATE activities_fts SET project = new.name
+            WHERE rowid IN (SELECT id FROM activities WHERE project_id = new.id);
+        END;",
+    )?;
+
+    Ok(())
+}
+
+// Cada resposta a um inquérito, com o modo de agendamento e o horário planejado
+// que a produziram. Uma atividade pode ter várias respostas (continuações).
+fn create_prompt_responses(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE prompt_responses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            activity_id INTEGER NOT NULL REFERENCES activities(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL,
+            schedule_mode TEXT NOT NULL,
+            planned_at TEXT,
+            answered_at TEXT NOT NULL
+        );
+        CREATE INDEX idx_prompt_responses_activity ON prompt_responses(activity_id);",
+    )?;
+
+    Ok(())
+}
+
+// Um registro por inquérito disparado pelo daemon, respondido ou não, para
+// calcular a taxa de resposta. `activity_id` aponta para a atividade criada ou
+// continuada (a última, num preenchimento de período).
+fn create_prompt_outcomes(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE prompt_outcomes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            outcome TEXT NOT NULL,
+            schedule_mode TEXT NOT NULL,
+            planned_at TEXT,
+            started_at TEXT NOT NULL,
+            finished_at TEXT NOT NULL,
+            exit_code INTEGER,
+            activity_id INTEGER REFERENCES activities(id) ON DELETE SET NULL
+        );
+        CREATE INDEX idx_prompt_outcomes_started ON prompt_outcomes(started_at);",
+    )?;
+
+    Ok(())
+}
+
+// Origem de cada atividade: 'answered' para respostas do usuário, 'away' e
+// 'repeated' para registros automáticos de inquéritos sem resposta
+fn add_activity_kind(tx: &Transaction) -> Result<()> {
+    tx.execute_batch("ALTER TABLE activities ADD COLUMN kind TEXT NOT NULL DEFAULT 'answered';")?;
+
+    Ok(())
+}
+
+// Cada entrega de webhook pendente ou feita. Uma entrega que falha é tentada de
+// novo em `next_attempt_at`, com espera crescente entre as tentativas.
+fn create_webhook_outbox(tx: &Transaction) -> Result<()> {
+    tx.execute_batch(
+        "CREATE TABLE webhook_outbox (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            url TEXT NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at TEXT NOT NULL,
+            last_error TEXT,
+            delivered_at TEXT
+        );
+        CREATE INDEX idx_webhook_outbox_pending ON webhook_outbox(delivered_at, next_attempt_at);",
+    )?;
+
+    Ok(())
+}
+
+// Até quando (em segundos Unix) um processo reservou a entrega para enviá-la.
+// Separado de `next_attempt_at` para que o `webhooks flush`, que ignora a espera
+// entre tentativas, ainda respeite a reserva de outro processo.
+fn add_webhook_lease(tx: &Transaction) -> Result<()> {
+    tx.execute_batch("ALTER TABLE webhook_outbox ADD COLUMN leased_until INTEGER;")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    // Banco como o da versão 1: uma amostra por resposta, sem fim
+    fn seed_v1(conn: &Connection, samples: &[(&str, &str, &str)]) {
+        conn.execute_batch(
+            "CREATE TABLE activities (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                description TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                date TEXT NOT NULL
+            );
+            PRAGMA user_version = 1;",
+        )
+        .unwrap();
+        for (description, timestamp, date) in samples {
+            conn.execute(
+                "INSERT INTO activities (description, timestamp, date) VALUES (?1, ?2, ?3)",
+                [description, timestamp, date],
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn converts_v1_samples_to_spans() {
+        let temp = TempDir::new("migrations-spans");
+        let mut conn = Connection::open(temp.join("activities.db")).unwrap();
+        seed_v1(
+            &conn,
+            &[
+                ("Codando", "2026-03-02T09:00:00-03:00", "2026-03-02"),
+                ("Codando", "2026-03-02T09:30:00-03:00", "2026-03-02"),
+                ("Reunião", "2026-03-02T10:00:00-03:00", "2026-03-02"),
+                ("Codando", "2026-03-02T11:00:00-03:00", "2026-03-02"),
+                ("Codando", "2026-03-02T11:45:00-03:00", "2026-03-02"),
+                ("Revisão", "2026-03-03T08:00:00-03:00", "2026-03-03"),
+                // Fim do horário de verão: o texto fica fora da ordem dos instantes
+                ("Email", "2026-10-25T01:30:00+02:00", "2026-10-25"),
+                ("Leitura", "2026-10-25T01:10:00+01:00", "2026-10-25"),
+            ],
+        );
+
+        run(&mut conn, &temp.join("activities.db")).unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        let spans: Vec<(i64, String, String, i64)> = conn
+            .prepare("SELECT id, description, end_timestamp, responses FROM activities ORDER BY id")
+            .unwrap()
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+
+        let expected = [
+            (1, "Codando", "2026-03-02T10:00:00-03:00", 2),
+            (3, "Reunião", "2026-03-02T11:00:00-03:00", 1),
+            (4, "Codando", "2026-03-02T11:45:00-03:00", 2),
+            // A última do dia termina na própria amostra
+            (6, "Revisão", "2026-03-03T08:00:00-03:00", 1),
+            (7, "Email", "2026-10-25T01:10:00+01:00", 1),
+            (8, "Leitura", "2026-10-25T01:10:00+01:00", 1),
+        ];
+        let spans: Vec<_> = spans
+            .iter()
+            .map(|(id, description, end, responses)| {
+                (*id, description.as_str(), end.as_str(), *responses)
+            })
+            .collect();
+        assert_eq!(spans, expected);
+
+        // Cópia de segurança feita antes de alterar os dados
+        let backups = std::fs::read_dir(temp.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().contains(".v1-"))
+            .count();
+        assert_eq!(backups, 1);
+    }
+
+    #[test]
+    fn new_database_needs_no_backup() {
+        let temp = TempDir::new("migrations-new");
+        let mut conn = Connection::open(temp.join("activities.db")).unwrap();
+
+        run(&mut conn, &temp.join("activities.db")).unwrap();
+
+        assert_eq!(current_version(&conn).unwrap(), latest_version());
+        assert_eq!(std::fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+}
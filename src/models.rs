@@ -1,12 +1,426 @@
-use chrono::{DateTime, Local, NaiveDate};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
 use serde::{Deserialize, Serialize};
 
+// Uma atividade é um intervalo: começa em `timestamp` e vai até `end_timestamp`,
+// que avança a cada confirmação ("ainda estou fazendo") e é fechado quando
+// a próxima atividade é registrada.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Activity {
     pub id: Option<i64>,
     pub description: String,
     pub timestamp: DateTime<Local>,
+    pub end_timestamp: DateTime<Local>,
+    pub responses: u32,
     pub date: NaiveDate,
+    #[serde(default)]
+    pub project: Option<Project>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub kind: ActivityKind,
+}
+
+// Origem de uma atividade: resposta do usuário ou registro automático de um
+// inquérito que expirou sem resposta (ver `UnansweredPolicy`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    #[default]
+    Answered,
+    // Entrada "Ausente" registrada no lugar da resposta
+    Away,
+    // Cópia da atividade anterior registrada no lugar da resposta
+    Repeated,
+}
+
+impl ActivityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::Answered => "answered",
+            ActivityKind::Away => "away",
+            ActivityKind::Repeated => "repeated",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "away" => ActivityKind::Away,
+            "repeated" => ActivityKind::Repeated,
+            _ => ActivityKind::Answered,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ActivityKind::Answered => "resposta do usuário",
+            ActivityKind::Away => "registrado como ausente",
+            ActivityKind::Repeated => "última atividade repetida",
+        }
+    }
+
+    pub fn is_automatic(&self) -> bool {
+        *self != ActivityKind::Answered
+    }
+}
+
+// Intervalo de datas, com início e fim inclusivos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Self {
+        if start <= end {
+            Self { start, end }
+        } else {
+            Self {
+                start: end,
+                end: start,
+            }
+        }
+    }
+
+    pub fn day(date: NaiveDate) -> Self {
+        Self::new(date, date)
+    }
+
+    // Semana ISO 8601 (segunda a domingo)
+    pub fn iso_week(year: i32, week: u32) -> Option<Self> {
+        let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)?;
+        let end = NaiveDate::from_isoywd_opt(year, week, Weekday::Sun)?;
+        Some(Self::new(start, end))
+    }
+
+    pub fn month(year: i32, month: u32) -> Option<Self> {
+        let start = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let next_month = if month == 12 {
+            NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+        } else {
+            NaiveDate::from_ymd_opt(year, month + 1, 1)?
+        };
+        Some(Self::new(start, next_month.pred_opt()?))
+    }
+
+    pub fn week_of(date: NaiveDate) -> Self {
+        let week = date.iso_week();
+        Self::iso_week(week.year(), week.week()).unwrap_or_else(|| Self::day(date))
+    }
+
+    pub fn month_of(date: NaiveDate) -> Self {
+        Self::month(date.year(), date.month()).unwrap_or_else(|| Self::day(date))
+    }
+}
+
+// Tempo total gasto em uma mesma descrição dentro de um intervalo de datas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DescriptionTotal {
+    pub description: String,
+    pub entries: u32,
+    pub responses: u32,
+    pub total_seconds: i64,
+}
+
+// Resumo de um dia: quantidade de registros, primeiro início e último fim
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailySummary {
+    pub date: NaiveDate,
+    pub entries: u32,
+    pub responses: u32,
+    pub total_seconds: i64,
+    pub first_start: DateTime<Local>,
+    pub last_end: DateTime<Local>,
+}
+
+// Respostas dadas aos inquéritos: novas atividades e confirmações de
+// que a atividade anterior continuava
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCounts {
+    pub new_entries: u32,
+    pub continuations: u32,
+}
+
+impl ResponseCounts {
+    pub fn total(&self) -> u32 {
+        self.new_entries + self.continuations
+    }
+}
+
+// Origem de um inquérito: o modo de agendamento que o disparou e o horário
+// planejado, gravados junto de cada resposta para análise posterior
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptContext {
+    pub schedule_mode: String,
+    pub planned_at: Option<DateTime<Local>>,
+}
+
+impl PromptContext {
+    // Inquérito aberto pelo usuário, fora da agenda
+    pub fn manual() -> Self {
+        Self {
+            schedule_mode: "manual".to_string(),
+            planned_at: None,
+        }
+    }
+
+    // Preenchimento de um período em que o computador estava suspenso
+    pub fn backfill() -> Self {
+        Self {
+            schedule_mode: "backfill".to_string(),
+            planned_at: None,
+        }
+    }
+}
+
+// Tipo de resposta dada a um inquérito
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseKind {
+    New,
+    Continued,
+}
+
+impl ResponseKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseKind::New => "new",
+            ResponseKind::Continued => "continued",
+        }
+    }
+}
+
+// Como terminou um inquérito. A janela informa o próprio resultado pelo código
+// de saída e, com `--report`, por uma linha JSON na saída padrão; tempo esgotado
+// e janela já aberta são decididos pelo daemon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InquiryOutcome {
+    // Registrou uma nova atividade
+    New,
+    // Confirmou que continua na atividade anterior
+    Continued,
+    // Preencheu um período sem respostas
+    Backfilled,
+    // Fechou a janela sem responder
+    Cancelled,
+    // Não abriu porque já havia uma janela de inquérito
+    Skipped,
+    // Encerrado após o tempo limite (pelo daemon, ou pelo próprio inquérito no modo `command`)
+    TimedOut,
+    // Encerrado porque o daemon foi parado com a janela aberta
+    Interrupted,
+    // Erro ao abrir a janela ou ao salvar
+    Failed,
+}
+
+impl InquiryOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InquiryOutcome::New => "new",
+            InquiryOutcome::Continued => "continued",
+            InquiryOutcome::Backfilled => "backfilled",
+            InquiryOutcome::Cancelled => "cancelled",
+            InquiryOutcome::Skipped => "skipped",
+            InquiryOutcome::TimedOut => "timed_out",
+            InquiryOutcome::Interrupted => "interrupted",
+            InquiryOutcome::Failed => "failed",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            InquiryOutcome::New => "nova atividade registrada",
+            InquiryOutcome::Continued => "atividade anterior continuada",
+            InquiryOutcome::Backfilled => "período sem respostas preenchido",
+            InquiryOutcome::Cancelled => "cancelado sem resposta",
+            InquiryOutcome::Skipped => "ignorado: janela de inquérito já aberta",
+            InquiryOutcome::TimedOut => "encerrado sem resposta (tempo esgotado)",
+            InquiryOutcome::Interrupted => "encerrado sem resposta (daemon parado)",
+            InquiryOutcome::Failed => "falhou",
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            InquiryOutcome::New => 0,
+            InquiryOutcome::Continued => 10,
+            InquiryOutcome::Backfilled => 11,
+            InquiryOutcome::Cancelled => 20,
+            InquiryOutcome::Skipped => 21,
+            InquiryOutcome::TimedOut | InquiryOutcome::Interrupted | InquiryOutcome::Failed => 1,
+        }
+    }
+
+    // Para processos que terminaram sem a linha JSON (ex.: versões antigas)
+    pub fn from_exit_code(code: i32) -> Self {
+        match code {
+            0 => InquiryOutcome::New,
+            10 => InquiryOutcome::Continued,
+            11 => InquiryOutcome::Backfilled,
+            20 => InquiryOutcome::Cancelled,
+            21 => InquiryOutcome::Skipped,
+            _ => InquiryOutcome::Failed,
+        }
+    }
+}
+
+// Resultado completo de um inquérito, como impresso pela janela com `--report`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InquiryReport {
+    pub outcome: InquiryOutcome,
+    // Atividades criadas ou continuadas pela resposta
+    #[serde(default)]
+    pub activity_ids: Vec<i64>,
+}
+
+impl InquiryReport {
+    pub fn new(outcome: InquiryOutcome) -> Self {
+        Self {
+            outcome,
+            activity_ids: Vec::new(),
+        }
+    }
+
+    // Resultado de um processo de inquérito: a última linha JSON da saída
+    // padrão, ou o código de saída quando não há nenhuma
+    pub fn from_process(stdout: &str, exit_code: Option<i32>) -> Self {
+        stdout
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str(line.trim()).ok())
+            .unwrap_or_else(|| {
+                Self::new(exit_code.map_or(InquiryOutcome::Failed, InquiryOutcome::from_exit_code))
+            })
+    }
+}
+
+// Resultado da busca textual, com o trecho da descrição que casou destacado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub activity: Activity,
+    pub snippet: String,
+}
+
+// Entrega de webhook ainda não confirmada pelo servidor
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub url: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: u32,
+}
+
+// Situação da fila de webhooks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebhookQueue {
+    pub pending: u32,
+    // Entregas que esgotaram as tentativas
+    pub abandoned: u32,
+    pub last_error: Option<String>,
+}
+
+// Projeto ou categoria a que uma atividade pertence
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub id: Option<i64>,
+    pub name: String,
+    pub color: String,
+    pub icon: Option<String>,
+}
+
+impl Project {
+    // Cores do Gruvbox usadas para novos projetos
+    const PALETTE: [&'static str; 6] = [
+        "#83a598", "#b8bb26", "#fabd2f", "#d3869b", "#8ec07c", "#fe8019",
+    ];
+
+    pub fn default_color(name: &str) -> String {
+        let hash = name.to_lowercase().bytes().fold(0usize, |hash, byte| {
+            hash.wrapping_mul(31).wrapping_add(byte as usize)
+        });
+        Self::PALETTE[hash % Self::PALETTE.len()].to_string()
+    }
+
+    pub fn rgb(&self) -> Option<[u8; 3]> {
+        let hex = self.color.strip_prefix('#')?;
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |range| u8::from_str_radix(hex.get(range)?, 16).ok();
+        Some([channel(0..2)?, channel(2..4)?, channel(4..6)?])
+    }
+
+    pub fn label(&self) -> String {
+        match &self.icon {
+            Some(icon) => format!("{icon} {}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+// Texto digitado pelo usuário, já separado em descrição, projeto e tags.
+// `#tag` e `@projeto` podem aparecer em qualquer posição do texto.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActivityInput {
+    pub description: String,
+    pub project: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl ActivityInput {
+    pub fn parse(text: &str) -> Self {
+        let mut input = ActivityInput::default();
+        let mut words = Vec::new();
+
+        for word in text.split_whitespace() {
+            if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+                input.add_tag(tag);
+            } else if let Some(project) = word.strip_prefix('@').filter(|p| !p.is_empty()) {
+                input.project = Some(project.to_string());
+            } else {
+                words.push(word);
+            }
+        }
+
+        input.description = words.join(" ");
+        input
+    }
+
+    // Lista de tags separadas por vírgula ou espaço, com ou sem `#`
+    pub fn add_tags_from_list(&mut self, list: &str) {
+        for tag in list.split(|c: char| c == ',' || c.is_whitespace()) {
+            self.add_tag(tag);
+        }
+    }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        let tag = tag.trim().trim_start_matches('#').replace(',', "");
+        if !tag.is_empty() && !self.tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+            self.tags.push(tag);
+        }
+    }
+}
+
+impl Activity {
+    pub fn duration(&self) -> Duration {
+        (self.end_timestamp - self.timestamp).max(Duration::zero())
+    }
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes < 60 {
+        format!("{minutes}min")
+    } else {
+        let hours = minutes / 60;
+        let remaining_minutes = minutes % 60;
+        if remaining_minutes == 0 {
+            format!("{hours}h")
+        } else {
+            format!("{hours}h{remaining_minutes}m")
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -19,5 +433,7 @@ pub enum Theme {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AppPage {
     Viewer,
+    Search,
+    Stats,
     Settings,
 }
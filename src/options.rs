@@ -0,0 +1,47 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// Opções globais da linha de comando, válidas para todos os subcomandos.
+// São definidas uma única vez em `main` e lidas por `AppConfig::load` e
+// `Database::new` (que apenas repassam o caminho a `AppConfig::load_from` e
+// `Database::open`) e pelo daemon, que as repassa aos processos de inquérito.
+#[derive(Debug, Clone, Default)]
+pub struct GlobalOptions {
+    pub config: Option<PathBuf>,
+    pub db: Option<PathBuf>,
+    pub verbose: u8,
+}
+
+static OPTIONS: OnceLock<GlobalOptions> = OnceLock::new();
+
+pub fn init(options: GlobalOptions) {
+    let _ = OPTIONS.set(options);
+}
+
+pub fn get() -> &'static GlobalOptions {
+    OPTIONS.get_or_init(GlobalOptions::default)
+}
+
+// Argumentos para que um processo filho use os mesmos arquivos que este
+pub fn forwarded_args() -> Vec<OsString> {
+    let options = get();
+    let mut args = Vec::new();
+
+    if let Some(config) = &options.config {
+        args.push(OsString::from("--config"));
+        args.push(config.clone().into_os_string());
+    }
+    if let Some(db) = &options.db {
+        args.push(OsString::from("--db"));
+        args.push(db.clone().into_os_string());
+    }
+    if options.verbose > 0 {
+        args.push(OsString::from(format!(
+            "-{}",
+            "v".repeat(options.verbose as usize)
+        )));
+    }
+
+    args
+}
@@ -0,0 +1,713 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Local};
+use eframe::egui;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use crate::config::{AppConfig, PrompterKind};
+use crate::database::Database;
+use crate::hooks::{self, HookEvent, Hooks};
+use crate::models::{
+    Activity, ActivityInput, ActivityKind, DateRange, InquiryOutcome, InquiryReport, PromptContext,
+    ResponseKind, Theme,
+};
+use crate::ui;
+use crate::webhooks::Webhooks;
+
+pub const WHAT_NOW: &str = "O que você está fazendo agora?";
+
+// Quantas descrições recentes oferecer como opção nos modos terminal e command
+const MAX_SUGGESTIONS: usize = 9;
+
+// O modo command encerra o seletor um pouco antes do tempo limite do daemon,
+// que só consegue encerrar o processo do inquérito, e não o seletor aberto por ele
+const COMMAND_MARGIN: std::time::Duration = std::time::Duration::from_secs(2);
+
+// Resultado do inquérito, compartilhado entre quem pergunta e `main`. Sem resposta,
+// o resultado é "cancelado" (inclusive ao fechar a janela pelo botão do sistema).
+#[derive(Clone)]
+pub struct ReportSink {
+    report: Arc<Mutex<InquiryReport>>,
+    print_json: bool,
+}
+
+impl ReportSink {
+    pub fn new(print_json: bool) -> Self {
+        Self {
+            report: Arc::new(Mutex::new(InquiryReport::new(InquiryOutcome::Cancelled))),
+            print_json,
+        }
+    }
+
+    pub fn set(&self, outcome: InquiryOutcome, activity_ids: Vec<i64>) {
+        if let Ok(mut report) = self.report.lock() {
+            *report = InquiryReport {
+                outcome,
+                activity_ids,
+            };
+        }
+    }
+
+    // Uma falha de quem pergunta só muda o resultado se o usuário ainda não respondeu
+    pub fn fail_if_unanswered(&self) {
+        if let Ok(mut report) = self.report.lock()
+            && report.outcome == InquiryOutcome::Cancelled
+        {
+            *report = InquiryReport::new(InquiryOutcome::Failed);
+        }
+    }
+
+    // Informa o resultado (linha JSON com `--report`) e devolve o código de saída
+    pub fn finish(&self) -> i32 {
+        let report = self
+            .report
+            .lock()
+            .map(|report| report.clone())
+            .unwrap_or_else(|_| InquiryReport::new(InquiryOutcome::Failed));
+
+        log::debug!("Resultado do inquérito: {}", report.outcome.label());
+        if self.print_json
+            && let Ok(json) = serde_json::to_string(&report)
+        {
+            println!("{json}");
+        }
+        report.outcome.exit_code()
+    }
+}
+
+// Primeira pergunta do inquérito
+pub enum Question {
+    // Nenhuma atividade a continuar: pergunta o que o usuário está fazendo
+    WhatNow,
+    // Pergunta se a última atividade de hoje continua
+    StillDoing(Activity),
+}
+
+impl Question {
+    pub fn text(&self) -> String {
+        match self {
+            Question::WhatNow => WHAT_NOW.to_string(),
+            Question::StillDoing(activity) => {
+                format!("Você ainda está fazendo \"{}\"?", activity.description)
+            }
+        }
+    }
+}
+
+// Banco, origem do inquérito e avisos (hooks, webhooks, resultado), usados do
+// mesmo jeito por todos os modos de perguntar
+pub struct InquirySession {
+    db: Database,
+    context: PromptContext,
+    report: ReportSink,
+    hooks: Hooks,
+    webhooks: Webhooks,
+}
+
+impl InquirySession {
+    pub fn new(context: PromptContext, report: ReportSink) -> Result<Self> {
+        Ok(Self {
+            db: Database::new()?,
+            context,
+            report,
+            hooks: Hooks::load(),
+            webhooks: Webhooks::load(),
+        })
+    }
+
+    // Aberto pelo daemon, o inquérito não executa hooks nem webhooks: o daemon
+    // executa depois que ele termina, já que pode encerrá-lo pelo tempo limite
+    // no meio de um hook
+    pub fn defer_notifications(&mut self) {
+        self.hooks = Hooks::default();
+        self.webhooks = Webhooks::default();
+    }
+
+    pub fn db(&self) -> &Database {
+        &self.db
+    }
+
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    pub fn report(&self) -> &ReportSink {
+        &self.report
+    }
+
+    pub fn question(&self) -> Result<Question> {
+        if self.db.count_activities_today()? == 0 {
+            return Ok(Question::WhatNow);
+        }
+
+        // Depois de um período registrado como ausente, não há o que continuar
+        Ok(self
+            .db
+            .get_last_activity_today()?
+            .filter(|activity| activity.kind != ActivityKind::Away)
+            .map_or(Question::WhatNow, Question::StillDoing))
+    }
+
+    // Início do período a preencher: `since` ou o fim da última atividade, o
+    // que for mais recente. `None` quando não há período perdido.
+    pub fn backfill_start(
+        &self,
+        since: Option<DateTime<Local>>,
+    ) -> Result<Option<DateTime<Local>>> {
+        let last_end = self
+            .db
+            .get_last_activity()?
+            .map(|activity| activity.end_timestamp);
+
+        let now = Local::now();
+        let start = match (since, last_end) {
+            (Some(since), Some(last_end)) => since.max(last_end),
+            (Some(since), None) => since,
+            (None, Some(last_end)) => last_end,
+            (None, None) => now - Duration::hours(1),
+        };
+
+        Ok((now - start >= Duration::minutes(1)).then_some(start))
+    }
+
+    // Descrições com mais tempo nos últimos 7 dias
+    pub fn suggestions(&self) -> Vec<String> {
+        let today = Local::now().date_naive();
+        let range = DateRange::new(today - Duration::days(6), today);
+        match self.db.time_per_description(range) {
+            Ok(totals) => totals
+                .into_iter()
+                .map(|total| total.description)
+                .filter(|description| description != "Ausente")
+                .take(MAX_SUGGESTIONS)
+                .collect(),
+            Err(e) => {
+                log::warn!("⚠️  {e:#}");
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn save_new(&self, input: &ActivityInput) -> Result<i64> {
+        let id = self.db.add_activity(input)?;
+        self.db
+            .record_prompt_response(id, ResponseKind::New, &self.context)?;
+        self.report.set(InquiryOutcome::New, vec![id]);
+        self.notify(HookEvent::ActivitySaved, id);
+        Ok(id)
+    }
+
+    // Se ainda está fazendo a mesma atividade, estende o intervalo atual
+    pub fn continue_activity(&self, id: i64) -> Result<()> {
+        self.db.continue_activity(id)?;
+        self.db
+            .record_prompt_response(id, ResponseKind::Continued, &self.context)?;
+        self.report.set(InquiryOutcome::Continued, vec![id]);
+        self.notify(HookEvent::ActivityContinued, id);
+        Ok(())
+    }
+
+    // Registra as atividades de um período sem respostas, cada uma a partir do
+    // horário informado
+    pub fn save_backfill(&self, mut entries: Vec<(DateTime<Local>, ActivityInput)>) -> Result<()> {
+        // Em ordem cronológica, cada atividade fecha a anterior
+        entries.sort_by_key(|(at, _)| *at);
+        let mut ids = Vec::new();
+        for (at, input) in &entries {
+            let id = self.db.add_activity_at(input, *at)?;
+            self.db
+                .record_prompt_response(id, ResponseKind::New, &self.context)?;
+            ids.push(id);
+        }
+        for id in &ids {
+            self.notify(HookEvent::ActivitySaved, *id);
+        }
+        self.report.set(InquiryOutcome::Backfilled, ids);
+        Ok(())
+    }
+
+    // Hooks e webhooks recebem a atividade como ficou gravada (com projeto, tags
+    // e fim). O hook roda em segundo plano, para não travar a janela.
+    fn notify(&self, event: HookEvent, id: i64) {
+        if !self.hooks.is_set(event) && !self.webhooks.is_enabled() {
+            return;
+        }
+        match self.db.get_activity(id) {
+            Ok(Some(activity)) => {
+                self.webhooks.send(&self.db, event, &activity);
+                self.hooks.run_in_background(event, Some(activity));
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("⚠️  Hook {} não executado: {e:#}", event.name()),
+        }
+    }
+}
+
+// Uma forma de fazer o inquérito. Todas seguem o mesmo fluxo: preencher o
+// período sem respostas, se houver; senão, perguntar se a última atividade
+// continua e, se não, o que o usuário está fazendo agora.
+pub trait Prompter {
+    fn run(
+        self: Box<Self>,
+        session: InquirySession,
+        backfill_start: Option<DateTime<Local>>,
+    ) -> Result<()>;
+}
+
+pub fn create(kind: PrompterKind, config: &AppConfig) -> Box<dyn Prompter> {
+    match kind {
+        PrompterKind::Gui => Box::new(GuiPrompter),
+        PrompterKind::Terminal => Box::new(TerminalPrompter {
+            tty: match config.prompter.tty.trim() {
+                "" => PathBuf::from("/dev/tty"),
+                tty => PathBuf::from(tty),
+            },
+        }),
+        PrompterKind::Command => Box::new(CommandPrompter {
+            command: config.prompter.command.trim().to_string(),
+            deadline: Instant::now()
+                + std::time::Duration::from_secs(config.get_inquiry_timeout_seconds())
+                    .saturating_sub(COMMAND_MARGIN),
+        }),
+    }
+}
+
+// A janela do acv-inq (`ui::InquiryApp`)
+struct GuiPrompter;
+
+impl Prompter for GuiPrompter {
+    fn run(
+        self: Box<Self>,
+        session: InquirySession,
+        backfill_start: Option<DateTime<Local>>,
+    ) -> Result<()> {
+        let size = if backfill_start.is_some() {
+            [520.0, 460.0]
+        } else {
+            [460.0, 380.0]
+        };
+        let app = ui::InquiryApp::new(session, backfill_start)?;
+
+        let options = eframe::NativeOptions {
+            viewport: egui::ViewportBuilder::default()
+                .with_inner_size(size)
+                .with_title("Activity Inquirer")
+                .with_resizable(false)
+                .with_close_button(true),
+            // .with_always_on_top(),
+            ..Default::default()
+        };
+
+        eframe::run_native(
+            "Activity Inquirer",
+            options,
+            Box::new(|cc| {
+                ui::theme::apply_theme(&cc.egui_ctx, Theme::default());
+                Box::new(app)
+            }),
+        )
+        .map_err(|e| anyhow::anyhow!("Erro ao fechar aplicação: {e}"))
+    }
+}
+
+// Modos que perguntam uma linha de cada vez
+trait LinePrompter {
+    // Faz a pergunta, com opções que o usuário pode escolher ou ignorar, e
+    // devolve a resposta; `None` quando o usuário desistiu
+    fn ask(&mut self, question: &str, options: &[String]) -> Result<Option<String>>;
+
+    // Confirma o que foi registrado, quando o modo tem onde mostrar
+    fn tell(&mut self, _message: &str) {}
+}
+
+// Resposta a "Você ainda está fazendo...?"
+enum Reply {
+    Yes,
+    No,
+    // Qualquer outro texto já é a nova atividade
+    Other(String),
+}
+
+impl Reply {
+    fn parse(answer: &str) -> Self {
+        match answer.trim().to_lowercase().as_str() {
+            "s" | "sim" | "y" | "yes" => Reply::Yes,
+            "n" | "não" | "nao" | "no" => Reply::No,
+            _ => Reply::Other(answer.trim().to_string()),
+        }
+    }
+}
+
+fn run_lines(
+    prompter: &mut dyn LinePrompter,
+    session: &InquirySession,
+    backfill_start: Option<DateTime<Local>>,
+) -> Result<()> {
+    let suggestions = session.suggestions();
+
+    // Uma linha só: uma atividade para o período inteiro
+    if let Some(start) = backfill_start {
+        let question = format!(
+            "Você esteve fora desde {}. O que você fez nesse período?",
+            start.format("%d/%m %H:%M")
+        );
+        let Some(input) = prompter.ask(&question, &suggestions)?.and_then(parse_input) else {
+            return Ok(());
+        };
+        session.save_backfill(vec![(start, input)])?;
+        prompter.tell("Atividade registrada!");
+        return Ok(());
+    }
+
+    let question = session.question()?;
+    if let Question::StillDoing(activity) = &question {
+        let options = ["Sim".to_string(), "Não".to_string()];
+        match prompter
+            .ask(&question.text(), &options)?
+            .map(|answer| Reply::parse(&answer))
+        {
+            None => return Ok(()),
+            Some(Reply::Yes) => {
+                if let Some(id) = activity.id {
+                    session.continue_activity(id)?;
+                    prompter.tell("Atividade continuada registrada!");
+                }
+                return Ok(());
+            }
+            Some(Reply::No) => {}
+            Some(Reply::Other(answer)) => return save_answer(prompter, session, answer),
+        }
+    }
+
+    match prompter.ask(WHAT_NOW, &suggestions)? {
+        Some(answer) => save_answer(prompter, session, answer),
+        None => Ok(()),
+    }
+}
+
+// Texto vazio conta como desistência
+fn parse_input(answer: String) -> Option<ActivityInput> {
+    Some(ActivityInput::parse(&answer)).filter(|input| !input.description.is_empty())
+}
+
+fn save_answer(
+    prompter: &mut dyn LinePrompter,
+    session: &InquirySession,
+    answer: String,
+) -> Result<()> {
+    if let Some(input) = parse_input(answer) {
+        session.save_new(&input)?;
+        prompter.tell("Atividade salva com sucesso!");
+    }
+    Ok(())
+}
+
+// Pergunta num terminal, que pode ser outro que não o do processo (ex.: um
+// painel do tmux, quando o daemon roda como serviço)
+struct TerminalPrompter {
+    tty: PathBuf,
+}
+
+impl Prompter for TerminalPrompter {
+    fn run(
+        mut self: Box<Self>,
+        session: InquirySession,
+        backfill_start: Option<DateTime<Local>>,
+    ) -> Result<()> {
+        run_lines(self.as_mut(), &session, backfill_start)
+    }
+}
+
+impl TerminalPrompter {
+    fn open(&self) -> Result<std::fs::File> {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&self.tty)
+            .with_context(|| format!("Falha ao abrir o terminal {:?}", self.tty))
+    }
+}
+
+impl LinePrompter for TerminalPrompter {
+    fn ask(&mut self, question: &str, options: &[String]) -> Result<Option<String>> {
+        let mut tty = self.open()?;
+
+        let mut prompt = format!("\n💬 {question}\n");
+        for (index, option) in options.iter().enumerate() {
+            prompt.push_str(&format!("  {}) {option}\n", index + 1));
+        }
+        if !options.is_empty() {
+            prompt.push_str("(número de uma opção ou texto; Enter vazio cancela)\n");
+        }
+        prompt.push_str("> ");
+        tty.write_all(prompt.as_bytes())
+            .and_then(|_| tty.flush())
+            .with_context(|| format!("Falha ao escrever no terminal {:?}", self.tty))?;
+
+        let mut line = String::new();
+        BufReader::new(tty)
+            .read_line(&mut line)
+            .with_context(|| format!("Falha ao ler do terminal {:?}", self.tty))?;
+
+        let answer = line.trim();
+        if answer.is_empty() {
+            return Ok(None);
+        }
+        let chosen = answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| options.get(number.checked_sub(1)?));
+        Ok(Some(chosen.map_or(answer, String::as_str).to_string()))
+    }
+
+    fn tell(&mut self, message: &str) {
+        if let Ok(mut tty) = self.open() {
+            let _ = writeln!(tty, "✅ {message}");
+        }
+    }
+}
+
+// Pergunta por um programa externo, como `rofi -dmenu` ou `zenity --entry`
+struct CommandPrompter {
+    command: String,
+    deadline: Instant,
+}
+
+impl Prompter for CommandPrompter {
+    fn run(
+        mut self: Box<Self>,
+        session: InquirySession,
+        backfill_start: Option<DateTime<Local>>,
+    ) -> Result<()> {
+        run_lines(self.as_mut(), &session, backfill_start).inspect_err(|_| {
+            if Instant::now() >= self.deadline {
+                session.report().set(InquiryOutcome::TimedOut, Vec::new());
+            }
+        })
+    }
+}
+
+impl LinePrompter for CommandPrompter {
+    fn ask(&mut self, question: &str, options: &[String]) -> Result<Option<String>> {
+        let mut child = hooks::shell(&self.command)
+            .env("ACV_QUESTION", question)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Falha ao executar \"{}\"", self.command))?;
+
+        // Como nos hooks, um comando que não lê a entrada não trava o inquérito
+        if let Some(mut stdin) = child.stdin.take() {
+            let input = options.join("\n") + "\n";
+            thread::spawn(move || {
+                let _ = stdin.write_all(input.as_bytes());
+            });
+        }
+        let stdout = hooks::read_output(&mut child.stdout);
+
+        let Some(status) = hooks::wait_until(&mut child, self.deadline)? else {
+            bail!("\"{}\" não respondeu dentro do tempo limite", self.command);
+        };
+        let output = stdout
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+
+        // rofi, dmenu e zenity saem com erro quando o usuário cancela
+        if !status.success() {
+            log::debug!("Comando do inquérito terminou com {status}");
+            return Ok(None);
+        }
+        Ok(output
+            .lines()
+            .next()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDb;
+    use chrono::Timelike;
+    use std::collections::VecDeque;
+
+    // Responde em ordem as respostas combinadas; `None` é o fim da entrada
+    struct FakePrompter {
+        answers: VecDeque<Option<&'static str>>,
+        asked: Vec<String>,
+        told: Vec<String>,
+    }
+
+    impl FakePrompter {
+        fn new(answers: &[Option<&'static str>]) -> Self {
+            Self {
+                answers: answers.iter().copied().collect(),
+                asked: Vec::new(),
+                told: Vec::new(),
+            }
+        }
+    }
+
+    impl LinePrompter for FakePrompter {
+        fn ask(&mut self, question: &str, _options: &[String]) -> Result<Option<String>> {
+            self.asked.push(question.to_string());
+            let answer = self
+                .answers
+                .pop_front()
+                .unwrap_or_else(|| panic!("pergunta inesperada: {question}"));
+            Ok(answer.map(String::from))
+        }
+
+        fn tell(&mut self, message: &str) {
+            self.told.push(message.to_string());
+        }
+    }
+
+    fn session(test: &TestDb) -> InquirySession {
+        InquirySession {
+            db: Database::open(&test.path()).unwrap(),
+            context: PromptContext::manual(),
+            report: ReportSink::new(false),
+            hooks: Hooks::default(),
+            webhooks: Webhooks::default(),
+        }
+    }
+
+    fn outcome(session: &InquirySession) -> InquiryOutcome {
+        session.report.report.lock().unwrap().outcome
+    }
+
+    fn run(
+        test: &TestDb,
+        answers: &[Option<&'static str>],
+        backfill_start: Option<DateTime<Local>>,
+    ) -> (FakePrompter, InquiryOutcome) {
+        let session = session(test);
+        let mut prompter = FakePrompter::new(answers);
+        run_lines(&mut prompter, &session, backfill_start).unwrap();
+        assert!(prompter.answers.is_empty(), "respostas não usadas");
+        (prompter, outcome(&session))
+    }
+
+    fn today(test: &TestDb) -> Vec<Activity> {
+        test.db
+            .get_activities_for_date(Local::now().date_naive())
+            .unwrap()
+    }
+
+    #[test]
+    fn first_answer_of_the_day_is_a_new_activity() {
+        let test = TestDb::new("prompter-new");
+
+        let (prompter, outcome) = run(&test, &[Some("revisão @cliente #pr")], None);
+
+        assert_eq!(prompter.asked, [WHAT_NOW]);
+        assert_eq!(prompter.told, ["Atividade salva com sucesso!"]);
+        assert_eq!(outcome, InquiryOutcome::New);
+        let activities = today(&test);
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].description, "revisão");
+        assert_eq!(activities[0].project.as_ref().unwrap().name, "cliente");
+        assert_eq!(activities[0].tags, ["pr"]);
+    }
+
+    #[test]
+    fn yes_continues_last_activity() {
+        let test = TestDb::new("prompter-continue");
+        test.db
+            .add_activity(&ActivityInput::parse("revisão"))
+            .unwrap();
+
+        let (prompter, outcome) = run(&test, &[Some(" Sim ")], None);
+
+        assert_eq!(prompter.asked, ["Você ainda está fazendo \"revisão\"?"]);
+        assert_eq!(outcome, InquiryOutcome::Continued);
+        let activities = today(&test);
+        assert_eq!(activities.len(), 1);
+        assert_eq!(activities[0].responses, 2);
+    }
+
+    #[test]
+    fn no_asks_for_the_new_activity() {
+        let test = TestDb::new("prompter-no");
+        test.db
+            .add_activity(&ActivityInput::parse("revisão"))
+            .unwrap();
+
+        let (prompter, outcome) = run(&test, &[Some("n"), Some("almoço")], None);
+
+        assert_eq!(prompter.asked.len(), 2);
+        assert_eq!(prompter.asked[1], WHAT_NOW);
+        assert_eq!(outcome, InquiryOutcome::New);
+        let descriptions: Vec<String> = today(&test)
+            .into_iter()
+            .map(|activity| activity.description)
+            .collect();
+        assert_eq!(descriptions, ["revisão", "almoço"]);
+    }
+
+    #[test]
+    fn other_reply_is_already_the_new_activity() {
+        let test = TestDb::new("prompter-other");
+        test.db
+            .add_activity(&ActivityInput::parse("revisão"))
+            .unwrap();
+
+        let (prompter, outcome) = run(&test, &[Some("reunião #time")], None);
+
+        assert_eq!(prompter.asked.len(), 1);
+        assert_eq!(outcome, InquiryOutcome::New);
+        assert_eq!(today(&test)[1].description, "reunião");
+    }
+
+    #[test]
+    fn empty_line_or_end_of_input_saves_nothing() {
+        let test = TestDb::new("prompter-skip");
+
+        for answers in [[Some("")], [Some("  #só-tag ")], [None]] {
+            let (prompter, outcome) = run(&test, &answers, None);
+            assert!(prompter.told.is_empty());
+            assert_eq!(outcome, InquiryOutcome::Cancelled);
+        }
+        assert!(today(&test).is_empty());
+
+        // Ao perguntar se continua, o fim da entrada encerra sem outra pergunta
+        test.db
+            .add_activity(&ActivityInput::parse("revisão"))
+            .unwrap();
+        let (prompter, outcome) = run(&test, &[None], None);
+        assert_eq!(prompter.asked.len(), 1);
+        assert_eq!(outcome, InquiryOutcome::Cancelled);
+        assert_eq!(today(&test)[0].responses, 1);
+    }
+
+    #[test]
+    fn backfill_records_one_activity_for_the_whole_period() {
+        let test = TestDb::new("prompter-backfill");
+        let start = (Local::now() - Duration::minutes(30))
+            .with_nanosecond(0)
+            .unwrap();
+
+        let (prompter, outcome) = run(&test, &[Some("reunião @cliente")], Some(start));
+
+        assert_eq!(prompter.asked.len(), 1);
+        assert!(prompter.asked[0].starts_with("Você esteve fora desde"));
+        assert_eq!(prompter.told, ["Atividade registrada!"]);
+        assert_eq!(outcome, InquiryOutcome::Backfilled);
+        let activity = test.db.get_last_activity().unwrap().unwrap();
+        assert_eq!(activity.description, "reunião");
+        assert_eq!(activity.timestamp, start);
+
+        // Sem resposta, o período continua vazio
+        let test = TestDb::new("prompter-backfill-skip");
+        let (_, outcome) = run(&test, &[None], Some(start));
+        assert_eq!(outcome, InquiryOutcome::Cancelled);
+        assert!(test.db.get_last_activity().unwrap().is_none());
+    }
+}
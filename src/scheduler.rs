@@ -0,0 +1,508 @@
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone, Timelike};
+use croner::Cron;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::config::{AppConfig, ScheduleConfig, ScheduleMode, TimeWindow};
+
+// Quantos dias à frente procurar a próxima janela ativa (cobre um ano inteiro
+// de datas ignoradas)
+const LOOKAHEAD_DAYS: i64 = 400;
+
+// Limite de horários candidatos testados contra o horário de trabalho, para uma
+// expressão cron que nunca cai dentro das janelas não travar o daemon
+const MAX_SLOT_ATTEMPTS: usize = 10_000;
+
+// Horários em que um inquérito pode acontecer
+enum Slots {
+    // A qualquer momento, contando o intervalo a partir do último inquérito
+    Interval,
+    // Múltiplos do intervalo contados a partir da meia-noite
+    Aligned,
+    Cron(Box<Cron>),
+    // `samples` horários sorteados em cada janela configurada, fora do almoço,
+    // separados por `min_gap`
+    Sampling { samples: u32, min_gap: Duration },
+}
+
+// Agenda do daemon: quando perguntar, a partir do modo de agendamento e do
+// horário de trabalho
+pub struct Schedule {
+    interval: Duration,
+    slots: Slots,
+    hours: Option<ScheduleConfig>,
+}
+
+impl Schedule {
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        let slots = match config.schedule.mode {
+            ScheduleMode::Interval => Slots::Interval,
+            ScheduleMode::Aligned => Slots::Aligned,
+            ScheduleMode::Cron => Slots::Cron(Box::new(config.schedule.parse_cron()?)),
+            ScheduleMode::Sampling => Slots::Sampling {
+                samples: config.schedule.samples_per_window,
+                min_gap: Duration::minutes(config.schedule.min_gap_minutes as i64),
+            },
+        };
+
+        Ok(Self {
+            interval: Duration::seconds(config.get_daemon_interval_seconds() as i64),
+            slots,
+            hours: config.schedule.enabled.then(|| config.schedule.clone()),
+        })
+    }
+
+    // Próximo inquérito depois de `last` (ou a partir de agora, se ainda não
+    // houve nenhum), dentro da próxima janela ativa. `None` quando a agenda não
+    // tem nenhum horário ativo.
+    pub fn next_inquiry(
+        &self,
+        last: Option<DateTime<Local>>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        if let Slots::Interval = self.slots {
+            let candidate = last
+                .map(|last| last + self.interval)
+                .unwrap_or(now)
+                .max(now);
+            return self.next_active(candidate);
+        }
+
+        // Nos modos alinhados, o primeiro inquérito espera o próximo horário
+        // da agenda, e um inquérito nunca se repete no mesmo horário
+        let from = last
+            .map(|last| last + Duration::seconds(1))
+            .unwrap_or(now)
+            .max(now);
+        let mut candidate = self.slot_at_or_after(from)?;
+        for _ in 0..MAX_SLOT_ATTEMPTS {
+            let active = self.next_active(candidate)?;
+            if active == candidate {
+                return Some(candidate);
+            }
+            candidate = self.slot_at_or_after(active)?;
+        }
+
+        None
+    }
+
+    fn slot_at_or_after(&self, from: DateTime<Local>) -> Option<DateTime<Local>> {
+        let from = from.with_nanosecond(0)?;
+        match &self.slots {
+            Slots::Interval => Some(from),
+            Slots::Aligned => {
+                let interval = self.interval.num_seconds().max(60);
+                let mut date = from.date_naive();
+                let mut elapsed = from.time().num_seconds_from_midnight() as i64;
+                loop {
+                    let slot = (elapsed + interval - 1) / interval * interval;
+                    if slot >= 24 * 60 * 60 {
+                        date = date.succ_opt()?;
+                        elapsed = 0;
+                        continue;
+                    }
+                    let time = NaiveTime::from_num_seconds_from_midnight_opt(slot as u32, 0)?;
+                    // Horários inexistentes (mudança de horário de verão) são pulados
+                    match local_datetime(date, time) {
+                        Some(at) if at >= from => return Some(at),
+                        _ => elapsed = slot + 1,
+                    }
+                }
+            }
+            Slots::Cron(cron) => cron.find_next_occurrence(&from, true).ok(),
+            Slots::Sampling { samples, min_gap } => {
+                let lunch = self.hours.as_ref().and_then(|hours| hours.lunch);
+                let first_day = from.date_naive();
+                (0..=LOOKAHEAD_DAYS)
+                    .map(|offset| first_day + Duration::days(offset))
+                    .find_map(|date| {
+                        self.windows_for(date)
+                            .into_iter()
+                            .flat_map(|window| {
+                                sample_times(date, window, lunch, *samples, *min_gap)
+                            })
+                            .filter(|at| *at >= from)
+                            .min()
+                    })
+            }
+        }
+    }
+
+    // Janelas em que os horários são sorteados, como configuradas (o almoço é
+    // descontado no sorteio). O modo exige horário de trabalho (ver
+    // `AppConfig::validate`); sem ele, não há o que sortear.
+    fn windows_for(&self, date: NaiveDate) -> Vec<TimeWindow> {
+        self.hours
+            .as_ref()
+            .map_or_else(Vec::new, |hours| configured_windows(hours, date))
+    }
+
+    // Primeiro instante a partir de `from` em que perguntar é permitido
+    pub fn next_active(&self, from: DateTime<Local>) -> Option<DateTime<Local>> {
+        let Some(hours) = &self.hours else {
+            return Some(from);
+        };
+
+        let first_day = from.date_naive();
+        (0..=LOOKAHEAD_DAYS)
+            .map(|offset| first_day + Duration::days(offset))
+            .flat_map(|date| {
+                active_windows(hours, date)
+                    .into_iter()
+                    .map(move |window| (date, window))
+            })
+            .find_map(|(date, window)| {
+                let start = local_datetime(date, window.start)?;
+                let end = local_datetime(date, window.end)?;
+                (from < end).then(|| start.max(from))
+            })
+    }
+}
+
+// Horário de um inquérito agendado, com a data quando não for hoje
+pub fn format_next(next: DateTime<Local>) -> String {
+    if next.date_naive() == Local::now().date_naive() {
+        next.format("%H:%M:%S").to_string()
+    } else {
+        next.format("%d/%m/%Y %H:%M:%S").to_string()
+    }
+}
+
+// Janelas do dia da semana, em ordem, ainda com o horário de almoço
+fn configured_windows(hours: &ScheduleConfig, date: NaiveDate) -> Vec<TimeWindow> {
+    if hours.skip_dates.contains(&date) {
+        return Vec::new();
+    }
+
+    let mut windows: Vec<TimeWindow> = hours.windows(date.weekday()).to_vec();
+    windows.sort_by_key(|window| window.start);
+    windows
+}
+
+// Janelas ativas de um dia, já sem o horário de almoço
+fn active_windows(hours: &ScheduleConfig, date: NaiveDate) -> Vec<TimeWindow> {
+    let windows = configured_windows(hours, date);
+    let Some(lunch) = hours.lunch else {
+        return windows;
+    };
+
+    windows
+        .into_iter()
+        .flat_map(|window| {
+            [
+                TimeWindow::new(window.start, window.end.min(lunch.start)),
+                TimeWindow::new(window.start.max(lunch.end), window.end),
+            ]
+        })
+        .filter(|window| window.start < window.end)
+        .collect()
+}
+
+// Sorteia `samples` horários na janela, fora do almoço, com pelo menos `min_gap`
+// entre eles. Os horários são uniformes entre todas as combinações válidas:
+// sorteia-se a folga livre de cada um e os intervalos mínimos são somados
+// depois. O almoço é retirado da linha do tempo antes do sorteio, então uma
+// janela que o atravessa recebe `samples` horários ao todo, e não de cada lado.
+// A semente vem da data e da janela, então o mesmo dia sempre gera os mesmos
+// horários, mesmo depois de reiniciar o daemon ou recarregar a configuração.
+fn sample_times(
+    date: NaiveDate,
+    window: TimeWindow,
+    lunch: Option<TimeWindow>,
+    samples: u32,
+    min_gap: Duration,
+) -> Vec<DateTime<Local>> {
+    // Parte do almoço dentro da janela
+    let lunch = lunch
+        .map(|lunch| TimeWindow::new(lunch.start.max(window.start), lunch.end.min(window.end)))
+        .filter(|lunch| lunch.start < lunch.end);
+    let lunch_length = lunch.map_or(Duration::zero(), |lunch| lunch.end - lunch.start);
+
+    let length = (window.end - window.start - lunch_length).num_minutes();
+    let gap = min_gap.num_minutes();
+    if length <= 0 || samples == 0 {
+        return Vec::new();
+    }
+
+    // Janelas curtas demais recebem só os horários que cabem
+    let count = if gap > 0 {
+        (samples as i64).min(length / gap + 1)
+    } else {
+        samples as i64
+    };
+    let slack = (length - gap * (count - 1)).max(1);
+
+    let seed = ((date.num_days_from_ce() as u64) << 32)
+        ^ ((window.start.num_seconds_from_midnight() as u64) << 16)
+        ^ window.end.num_seconds_from_midnight() as u64;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut offsets: Vec<i64> = (0..count).map(|_| rng.gen_range(0..slack)).collect();
+    offsets.sort_unstable();
+
+    offsets
+        .into_iter()
+        .zip(0..)
+        .filter_map(|(offset, index)| {
+            let mut time = window.start + Duration::minutes(offset + index * gap);
+            // Do início do almoço em diante, a linha do tempo continua depois dele
+            if let Some(lunch) = lunch
+                && time >= lunch.start
+            {
+                time += lunch_length;
+            }
+            local_datetime(date, time)
+        })
+        .collect()
+}
+
+fn local_datetime(date: NaiveDate, time: NaiveTime) -> Option<DateTime<Local>> {
+    Local.from_local_datetime(&date.and_time(time)).earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn workday() -> TimeWindow {
+        TimeWindow::new(time(9, 0), time(18, 0))
+    }
+
+    fn lunch() -> Option<TimeWindow> {
+        Some(TimeWindow::new(time(12, 0), time(13, 0)))
+    }
+
+    fn at(date: NaiveDate, hour: u32, minute: u32) -> DateTime<Local> {
+        local_datetime(date, time(hour, minute)).unwrap()
+    }
+
+    // Segunda-feira
+    fn monday() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+    }
+
+    // Horário de trabalho padrão (9h às 18h nos dias úteis, almoço das 12h às 13h)
+    fn working_hours(mode: ScheduleMode, interval_minutes: u64) -> AppConfig {
+        let mut config = AppConfig {
+            daemon_interval_minutes: interval_minutes,
+            ..AppConfig::default()
+        };
+        config.schedule.mode = mode;
+        config.schedule.enabled = true;
+        config
+    }
+
+    fn cron(expression: &str) -> AppConfig {
+        let mut config = working_hours(ScheduleMode::Cron, 60);
+        config.schedule.cron = expression.to_string();
+        config
+    }
+
+    #[test]
+    fn sampling_is_deterministic_per_date() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let gap = Duration::minutes(20);
+
+        let first = sample_times(date, workday(), lunch(), 4, gap);
+        let again = sample_times(date, workday(), lunch(), 4, gap);
+        assert_eq!(first, again);
+
+        // Outro dia, outro sorteio
+        let other = sample_times(date.succ_opt().unwrap(), workday(), lunch(), 4, gap);
+        let first_times: Vec<NaiveTime> = first.iter().map(|at| at.time()).collect();
+        let other_times: Vec<NaiveTime> = other.iter().map(|at| at.time()).collect();
+        assert_ne!(first_times, other_times);
+    }
+
+    #[test]
+    fn samples_each_configured_window_once_outside_lunch() {
+        let gap = Duration::minutes(30);
+        let first = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        for offset in 0..200 {
+            let date = first + Duration::days(offset);
+            let times: Vec<NaiveTime> = sample_times(date, workday(), lunch(), 3, gap)
+                .iter()
+                .map(|at| at.time())
+                .collect();
+
+            // A janela atravessa o almoço, mas recebe 3 horários ao todo
+            assert_eq!(times.len(), 3, "{date}: {times:?}");
+            for time in &times {
+                assert!(*time >= workday().start && *time < workday().end);
+                assert!(
+                    !(lunch().unwrap().start..lunch().unwrap().end).contains(time),
+                    "{date}: {time} no almoço"
+                );
+            }
+            for pair in times.windows(2) {
+                assert!(pair[1] - pair[0] >= gap, "{date}: {times:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn short_window_gets_only_what_fits() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let window = TimeWindow::new(time(9, 0), time(10, 0));
+
+        let times = sample_times(date, window, None, 10, Duration::minutes(20));
+        assert_eq!(times.len(), 4);
+
+        // Janela inteira no almoço: nada a sortear
+        let window = TimeWindow::new(time(12, 0), time(13, 0));
+        assert!(sample_times(date, window, lunch(), 4, Duration::minutes(20)).is_empty());
+    }
+
+    #[test]
+    fn schedule_asks_samples_per_window_each_day() {
+        let mut config = AppConfig::default();
+        config.schedule.mode = ScheduleMode::Sampling;
+        config.schedule.enabled = true;
+        config.schedule.samples_per_window = 4;
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        // Segunda-feira
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let start = local_datetime(date, NaiveTime::MIN).unwrap();
+
+        let mut asked = Vec::new();
+        let mut last = None;
+        while let Some(next) = schedule.next_inquiry(last, start) {
+            if next.date_naive() != date {
+                break;
+            }
+            asked.push(next);
+            last = Some(next);
+        }
+
+        assert_eq!(asked.len(), 4, "{asked:?}");
+    }
+
+    #[test]
+    fn aligned_skips_slots_in_lunch_break() {
+        // Múltiplos de 45min desde a meia-noite: 11:15, 12:00, 12:45, 13:30...
+        let schedule = Schedule::from_config(&working_hours(ScheduleMode::Aligned, 45)).unwrap();
+        let date = monday();
+
+        assert_eq!(
+            schedule.next_inquiry(None, at(date, 11, 40)),
+            Some(at(date, 13, 30))
+        );
+        assert_eq!(
+            schedule.next_inquiry(Some(at(date, 11, 15)), at(date, 11, 20)),
+            Some(at(date, 13, 30))
+        );
+        assert_eq!(
+            schedule.next_inquiry(Some(at(date, 13, 30)), at(date, 13, 30)),
+            Some(at(date, 14, 15))
+        );
+    }
+
+    #[test]
+    fn aligned_moves_to_next_working_day_after_window_end() {
+        let mut config = working_hours(ScheduleMode::Aligned, 45);
+        let tuesday = monday().succ_opt().unwrap();
+        let wednesday = tuesday.succ_opt().unwrap();
+        config.schedule.skip_dates = vec![tuesday];
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        // 18:00 é um horário alinhado, mas a janela termina nele
+        assert_eq!(
+            schedule.next_inquiry(None, at(monday(), 17, 50)),
+            Some(at(wednesday, 9, 0))
+        );
+
+        // Sexta-feira à noite: o fim de semana não tem janelas
+        let friday = monday() + Duration::days(4);
+        assert_eq!(
+            schedule.next_inquiry(None, at(friday, 19, 0)),
+            Some(at(monday() + Duration::days(7), 9, 0))
+        );
+    }
+
+    #[test]
+    fn cron_only_fires_inside_working_hours() {
+        let schedule = Schedule::from_config(&cron("0,30 * * * *")).unwrap();
+        let date = monday();
+
+        // 12:00 e 12:30 caem no almoço; 13:00 é o fim dele
+        assert_eq!(
+            schedule.next_inquiry(None, at(date, 11, 40)),
+            Some(at(date, 13, 0))
+        );
+        // O mesmo horário não se repete
+        assert_eq!(
+            schedule.next_inquiry(Some(at(date, 13, 0)), at(date, 13, 0)),
+            Some(at(date, 13, 30))
+        );
+        assert_eq!(
+            schedule.next_inquiry(Some(at(date, 17, 30)), at(date, 17, 30)),
+            Some(at(date.succ_opt().unwrap(), 9, 0))
+        );
+    }
+
+    #[test]
+    fn cron_skips_ignored_dates() {
+        let mut config = cron("0 10 * * *");
+        let tuesday = monday().succ_opt().unwrap();
+        config.schedule.skip_dates = vec![tuesday];
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        assert_eq!(
+            schedule.next_inquiry(None, at(monday(), 11, 0)),
+            Some(at(tuesday.succ_opt().unwrap(), 10, 0))
+        );
+    }
+
+    #[test]
+    fn cron_outside_every_window_never_fires() {
+        // Só aos sábados, que não têm janelas
+        let schedule = Schedule::from_config(&cron("0 10 * * 6")).unwrap();
+        assert_eq!(schedule.next_inquiry(None, at(monday(), 9, 0)), None);
+    }
+
+    // Fuso com horário de verão: em 29/03/2026 o relógio pula de 01:00 para 02:00
+    const DST_ZONE: &str = "Europe/Lisbon";
+
+    #[test]
+    fn aligned_skips_slots_missing_in_dst_gap() {
+        // O fuso local vem de TZ, então o teste roda de novo em outro processo com
+        // o fuso definido, sem alterar o ambiente dos demais testes
+        if std::env::var("TZ").as_deref() != Ok(DST_ZONE) {
+            let output = std::process::Command::new(std::env::current_exe().unwrap())
+                .args([
+                    "--exact",
+                    "scheduler::tests::aligned_skips_slots_missing_in_dst_gap",
+                ])
+                .env("TZ", DST_ZONE)
+                .output()
+                .unwrap();
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            assert!(
+                output.status.success() && stdout.contains("1 passed"),
+                "{stdout}"
+            );
+            return;
+        }
+
+        let date = NaiveDate::from_ymd_opt(2026, 3, 29).unwrap();
+        assert_eq!(local_datetime(date, time(1, 30)), None);
+
+        let mut config = AppConfig {
+            daemon_interval_minutes: 30,
+            ..AppConfig::default()
+        };
+        config.schedule.mode = ScheduleMode::Aligned;
+        let schedule = Schedule::from_config(&config).unwrap();
+
+        // 01:00 e 01:30 não existem neste dia
+        let next = schedule.next_inquiry(None, at(date, 0, 40)).unwrap();
+        assert_eq!(next, at(date, 2, 0));
+        assert_eq!(next - at(date, 0, 30), Duration::minutes(30));
+    }
+}
@@ -0,0 +1,583 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::Local;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::cli::{self, ActivityRecord};
+use crate::config::AppConfig;
+use crate::database::Database;
+use crate::hooks::Hooks;
+use crate::models::{DailySummary, DateRange, DescriptionTotal, ResponseCounts};
+use crate::webhooks::Webhooks;
+
+// A API só escuta na máquina local
+const BIND_ADDRESS: &str = "127.0.0.1";
+
+// Tamanho máximo do corpo de `POST /activities`
+const MAX_BODY: u64 = 64 * 1024;
+
+// Resposta de erro: status HTTP e mensagem devolvida em {"error": "..."}
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    // Erros de validação da entrada (datas, descrição vazia...)
+    fn bad_request(error: anyhow::Error) -> Self {
+        Self::new(400, format!("{error:#}"))
+    }
+
+    fn internal(error: anyhow::Error) -> Self {
+        warn!("⚠️  Erro na API: {error:#}");
+        Self::new(500, format!("{error:#}"))
+    }
+}
+
+type ApiResult = std::result::Result<(u16, serde_json::Value), ApiError>;
+
+// Corpo de `POST /activities`; `description` aceita #tag e @projeto, como no `log`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NewActivity {
+    description: String,
+    #[serde(default)]
+    at: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Stats {
+    range: DateRange,
+    total_seconds: i64,
+    responses: ResponseCounts,
+    per_description: Vec<DescriptionTotal>,
+    per_day: Vec<DailySummary>,
+}
+
+struct Api {
+    db: Database,
+    port: u16,
+    token: Option<String>,
+    hooks: Hooks,
+    webhooks: Webhooks,
+}
+
+// Atende a API até o processo ser encerrado. `port` e `token` substituem os
+// valores da seção [server] do config.toml.
+pub fn run(port: Option<u16>, token: Option<String>) -> Result<()> {
+    let config = AppConfig::load()?;
+    let port = port.unwrap_or(config.server.port);
+    let token = token
+        .or(Some(config.server.token))
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty());
+
+    let server = Server::http((BIND_ADDRESS, port))
+        .map_err(|e| anyhow!("Falha ao abrir a API em {BIND_ADDRESS}:{port}: {e}"))?;
+    let api = Api {
+        db: Database::new()?,
+        port,
+        token,
+        hooks: Hooks::new(config.hooks.clone()),
+        webhooks: Webhooks::new(config.webhooks.clone()),
+    };
+
+    println!("🌐 API local em http://{BIND_ADDRESS}:{port}");
+    if api.token.is_some() {
+        println!("🔑 Requisições exigem o cabeçalho Authorization: Bearer <token>");
+    }
+
+    serve(&server, &api);
+    Ok(())
+}
+
+fn serve(server: &Server, api: &Api) {
+    for mut request in server.incoming_requests() {
+        let (status, body) = match api.handle(&mut request) {
+            Ok(response) => response,
+            Err(e) => (e.status, serde_json::json!({ "error": e.message })),
+        };
+        debug!(
+            "🌐 {} {} → {status}",
+            request.method(),
+            request.url().split('?').next().unwrap_or_default()
+        );
+
+        let mut response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(header("Content-Type", "application/json; charset=utf-8"));
+        if status == 401 {
+            response = response.with_header(header("WWW-Authenticate", "Bearer"));
+        }
+        if let Err(e) = request.respond(response) {
+            warn!("⚠️  Falha ao responder requisição da API: {e}");
+        }
+    }
+}
+
+impl Api {
+    fn handle(&self, request: &mut Request) -> ApiResult {
+        self.check_host(request)?;
+
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let path = path.trim_end_matches('/');
+        let query = parse_query(query);
+
+        if path == "/health" {
+            return Ok((
+                200,
+                serde_json::json!({ "status": "ok", "version": env!("CARGO_PKG_VERSION") }),
+            ));
+        }
+        self.check_token(request)?;
+
+        match (request.method(), path) {
+            (Method::Get, "/activities") => self.list_activities(&query),
+            (Method::Post, "/activities") => self.add_activity(request),
+            (Method::Get, "/activities/current") => self.current_activity(),
+            (Method::Get, "/stats") => self.stats(&query),
+            (_, "/activities" | "/activities/current" | "/stats") => {
+                Err(ApiError::new(405, "Método não permitido"))
+            }
+            _ => Err(ApiError::new(404, format!("Rota não encontrada: {path}"))),
+        }
+    }
+
+    // Recusa nomes de host de fora (ex.: uma página web que faz o próprio
+    // domínio apontar para 127.0.0.1 para ler a API pelo navegador)
+    fn check_host(&self, request: &Request) -> std::result::Result<(), ApiError> {
+        let Some(host) = header_value(request, "Host") else {
+            return Ok(());
+        };
+        let allowed = ["127.0.0.1", "localhost"]
+            .iter()
+            .any(|name| host == *name || host == format!("{name}:{}", self.port));
+        if allowed {
+            Ok(())
+        } else {
+            Err(ApiError::new(403, format!("Host não permitido: {host}")))
+        }
+    }
+
+    fn check_token(&self, request: &Request) -> std::result::Result<(), ApiError> {
+        let Some(token) = &self.token else {
+            return Ok(());
+        };
+        let given = header_value(request, "Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim);
+        if given.is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes())) {
+            Ok(())
+        } else {
+            Err(ApiError::new(401, "Token ausente ou inválido"))
+        }
+    }
+
+    // `?date=`, `?from=&to=`, `?week=` ou `?month=`, como no `list`; sem
+    // nenhum deles, o dia de hoje
+    fn range(query: &[(String, String)]) -> std::result::Result<DateRange, ApiError> {
+        let param = |name: &str| {
+            query
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        if let Some(date) = param("date") {
+            return cli::parse_date(date)
+                .map(DateRange::day)
+                .map_err(ApiError::bad_request);
+        }
+        cli::parse_range(param("from"), param("to"), param("week"), param("month"))
+            .map(|range| range.unwrap_or_else(|| DateRange::day(Local::now().date_naive())))
+            .map_err(ApiError::bad_request)
+    }
+
+    fn list_activities(&self, query: &[(String, String)]) -> ApiResult {
+        let activities = self
+            .db
+            .get_activities_between(Self::range(query)?)
+            .map_err(ApiError::internal)?;
+        let records: Vec<ActivityRecord> = activities.iter().map(ActivityRecord::new).collect();
+        Ok((200, to_json(&records)?))
+    }
+
+    fn add_activity(&self, request: &mut Request) -> ApiResult {
+        // Um formulário de outro site não consegue mandar JSON sem a permissão
+        // do CORS, que esta API nunca dá
+        let is_json = header_value(request, "Content-Type")
+            .is_some_and(|value| value.starts_with("application/json"));
+        if !is_json {
+            return Err(ApiError::new(415, "Use Content-Type: application/json"));
+        }
+
+        let mut body = String::new();
+        request
+            .as_reader()
+            .take(MAX_BODY + 1)
+            .read_to_string(&mut body)
+            .map_err(|e| ApiError::new(400, format!("Falha ao ler corpo da requisição: {e}")))?;
+        if body.len() as u64 > MAX_BODY {
+            return Err(ApiError::new(413, "Corpo da requisição muito grande"));
+        }
+        let new: NewActivity = serde_json::from_str(&body)
+            .map_err(|e| ApiError::new(400, format!("JSON inválido: {e}")))?;
+
+        let tags = new.tags.join(",");
+        let (input, at) = cli::prepare_log(
+            &new.description,
+            new.at.as_deref(),
+            new.project.as_deref(),
+            Some(tags.as_str()).filter(|tags| !tags.is_empty()),
+        )
+        .map_err(ApiError::bad_request)?;
+        // Um hook ou serviço lento não deve travar as próximas requisições: os
+        // dois rodam em threads que ninguém espera
+        let activity = cli::record_activity(&self.db, &self.hooks, &self.webhooks, &input, at)
+            .map_err(ApiError::internal)?;
+        self.webhooks.deliver_in_background();
+
+        Ok((201, to_json(&ActivityRecord::new(&activity))?))
+    }
+
+    fn current_activity(&self) -> ApiResult {
+        match self
+            .db
+            .get_last_activity_today()
+            .map_err(ApiError::internal)?
+        {
+            Some(activity) => Ok((200, to_json(&ActivityRecord::new(&activity))?)),
+            None => Err(ApiError::new(404, "Nenhuma atividade registrada hoje")),
+        }
+    }
+
+    fn stats(&self, query: &[(String, String)]) -> ApiResult {
+        let range = Self::range(query)?;
+        let per_description = self
+            .db
+            .time_per_description(range)
+            .map_err(ApiError::internal)?;
+        let stats = Stats {
+            range,
+            total_seconds: per_description
+                .iter()
+                .map(|total| total.total_seconds)
+                .sum(),
+            responses: self.db.response_counts(range).map_err(ApiError::internal)?,
+            per_description,
+            per_day: self.db.daily_summaries(range).map_err(ApiError::internal)?,
+        };
+        Ok((200, to_json(&stats)?))
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> std::result::Result<serde_json::Value, ApiError> {
+    serde_json::to_value(value)
+        .context("Falha ao serializar resposta")
+        .map_err(ApiError::internal)
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("cabeçalho HTTP válido")
+}
+
+fn header_value<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv(name))
+        .map(|header| header.value.as_str())
+}
+
+// Compara o token sem parar no primeiro byte diferente
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// Pares `chave=valor` da query string, decodificados
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| std::str::from_utf8(hex).ok())
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+    use std::io::Write;
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::thread;
+
+    const TOKEN: &str = "segredo";
+
+    // API numa porta livre, com um banco temporário
+    struct TestApi {
+        server: Arc<Server>,
+        port: u16,
+        thread: Option<thread::JoinHandle<()>>,
+        _dir: TempDir,
+    }
+
+    impl TestApi {
+        fn start(name: &str) -> Self {
+            let dir = TempDir::new(&format!("server-{name}"));
+            let server = Arc::new(Server::http((BIND_ADDRESS, 0)).unwrap());
+            let port = server.server_addr().to_ip().unwrap().port();
+            let api = Api {
+                db: Database::open(&dir.join("activities.db")).unwrap(),
+                port,
+                token: Some(TOKEN.to_string()),
+                hooks: Hooks::default(),
+                webhooks: Webhooks::default(),
+            };
+
+            let thread = thread::spawn({
+                let server = Arc::clone(&server);
+                move || serve(&server, &api)
+            });
+
+            Self {
+                server,
+                port,
+                thread: Some(thread),
+                _dir: dir,
+            }
+        }
+
+        // Requisição HTTP crua, para controlar todos os cabeçalhos (inclusive o Host)
+        fn send(
+            &self,
+            method: &str,
+            path: &str,
+            headers: &[(&str, &str)],
+            body: &str,
+        ) -> (u16, String) {
+            let mut stream = TcpStream::connect((BIND_ADDRESS, self.port)).unwrap();
+            let mut request = format!("{method} {path} HTTP/1.1\r\nConnection: close\r\n");
+            if !headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case("Host"))
+            {
+                request.push_str(&format!("Host: 127.0.0.1:{}\r\n", self.port));
+            }
+            for (name, value) in headers {
+                request.push_str(&format!("{name}: {value}\r\n"));
+            }
+            request.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+            stream.write_all(request.as_bytes()).unwrap();
+
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+            (status, body.to_string())
+        }
+
+        fn get(&self, path: &str) -> (u16, serde_json::Value) {
+            let (status, body) = self.send("GET", path, &[("Authorization", "Bearer segredo")], "");
+            (status, serde_json::from_str(&body).unwrap())
+        }
+
+        fn post(&self, body: &str) -> (u16, serde_json::Value) {
+            let (status, body) = self.send(
+                "POST",
+                "/activities",
+                &[
+                    ("Authorization", "Bearer segredo"),
+                    ("Content-Type", "application/json"),
+                ],
+                body,
+            );
+            (status, serde_json::from_str(&body).unwrap())
+        }
+    }
+
+    impl Drop for TestApi {
+        fn drop(&mut self) {
+            self.server.unblock();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    #[test]
+    fn parse_query_decodes_pairs() {
+        let pairs = |query: &str| parse_query(query);
+        assert_eq!(
+            pairs("date=ontem&week=2024-W05"),
+            [
+                ("date".to_string(), "ontem".to_string()),
+                ("week".to_string(), "2024-W05".to_string())
+            ]
+        );
+        assert_eq!(
+            pairs("a&&b=&=c"),
+            [
+                ("a".to_string(), String::new()),
+                ("b".to_string(), String::new()),
+                (String::new(), "c".to_string())
+            ]
+        );
+        assert!(pairs("").is_empty());
+    }
+
+    #[test]
+    fn percent_decode_handles_malformed_escapes() {
+        assert_eq!(
+            percent_decode("revis%C3%A3o+do+c%C3%B3digo"),
+            "revisão do código"
+        );
+        assert_eq!(percent_decode("a%2Bb%20c"), "a+b c");
+        // Escapes inválidos ou cortados ficam como estão
+        assert_eq!(percent_decode("100%zz"), "100%zz");
+        assert_eq!(percent_decode("50%"), "50%");
+        assert_eq!(percent_decode("%4"), "%4");
+        assert_eq!(percent_decode("%%41"), "%A");
+        // UTF-8 inválido não quebra a decodificação
+        assert_eq!(percent_decode("%C3"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_token() {
+        assert!(constant_time_eq(b"segredo", b"segredo"));
+        assert!(!constant_time_eq(b"segredo", b"segredO"));
+        assert!(!constant_time_eq(b"segredo", b"segred"));
+        assert!(!constant_time_eq(b"", b"segredo"));
+        assert!(constant_time_eq(b"", b""));
+    }
+
+    #[test]
+    fn health_needs_no_token() {
+        let api = TestApi::start("health");
+        let (status, body) = api.send("GET", "/health", &[], "");
+        assert_eq!(status, 200);
+        assert!(body.contains("\"status\":\"ok\""));
+    }
+
+    #[test]
+    fn rejects_missing_or_wrong_token() {
+        let api = TestApi::start("token");
+
+        let (status, _) = api.send("GET", "/activities", &[], "");
+        assert_eq!(status, 401);
+        let (status, _) = api.send(
+            "GET",
+            "/activities",
+            &[("Authorization", "Bearer outro")],
+            "",
+        );
+        assert_eq!(status, 401);
+        let (status, _) = api.send("GET", "/activities", &[("Authorization", "segredo")], "");
+        assert_eq!(status, 401);
+
+        assert_eq!(api.get("/activities").0, 200);
+    }
+
+    #[test]
+    fn rejects_foreign_host() {
+        let api = TestApi::start("host");
+
+        let headers = [("Host", "exemplo.com"), ("Authorization", "Bearer segredo")];
+        assert_eq!(api.send("GET", "/activities", &headers, "").0, 403);
+        assert_eq!(
+            api.send("GET", "/health", &[("Host", "exemplo.com")], "").0,
+            403
+        );
+
+        let port = format!("localhost:{}", api.port);
+        assert_eq!(api.send("GET", "/health", &[("Host", &port)], "").0, 200);
+    }
+
+    #[test]
+    fn unknown_routes_and_methods() {
+        let api = TestApi::start("routes");
+
+        assert_eq!(api.get("/nada").0, 404);
+        let auth = [("Authorization", "Bearer segredo")];
+        assert_eq!(api.send("DELETE", "/activities", &auth, "").0, 405);
+        assert_eq!(api.send("PUT", "/stats", &auth, "").0, 405);
+    }
+
+    #[test]
+    fn post_records_activity_and_reads_it_back() {
+        let api = TestApi::start("post");
+
+        let (status, created) =
+            api.post(r#"{"description": "Revisão @cliente", "at": "00:00", "tags": ["código"]}"#);
+        assert_eq!(status, 201);
+        assert_eq!(created["description"], "Revisão");
+        assert_eq!(created["project"]["name"], "cliente");
+        assert_eq!(created["tags"], serde_json::json!(["código"]));
+
+        let (status, listed) = api.get("/activities");
+        assert_eq!(status, 200);
+        assert_eq!(listed.as_array().unwrap().len(), 1);
+        assert_eq!(listed[0]["id"], created["id"]);
+
+        let (status, current) = api.get("/activities/current");
+        assert_eq!(status, 200);
+        assert_eq!(current["id"], created["id"]);
+    }
+
+    #[test]
+    fn post_validates_body() {
+        let api = TestApi::start("post-invalid");
+        let auth = ("Authorization", "Bearer segredo");
+
+        let (status, _) = api.send("POST", "/activities", &[auth], r#"{"description": "x"}"#);
+        assert_eq!(status, 415);
+        assert_eq!(api.post("{").0, 400);
+        assert_eq!(api.post(r#"{"description": "  "}"#).0, 400);
+        assert_eq!(api.post(r#"{"description": "x", "extra": 1}"#).0, 400);
+        assert_eq!(api.get("/activities/current").0, 404);
+    }
+}
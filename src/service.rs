@@ -0,0 +1,483 @@
+use anyhow::{Context, Result, bail};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::AppConfig;
+use crate::instance;
+use crate::options;
+
+const UNIT_NAME: &str = "activity-inquirer.service";
+const AUTOSTART_NAME: &str = "activity-inquirer-daemon.desktop";
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::Subcommand)]
+pub enum ServiceAction {
+    /// Instalar o serviço systemd de usuário e, com auto_start_daemon, a entrada de inicialização automática
+    Install {
+        /// Ativar auto_start_daemon no config.toml antes de instalar
+        #[arg(long)]
+        auto_start: bool,
+    },
+    /// Remover o serviço e a entrada de inicialização automática
+    Uninstall,
+    /// Mostrar o que está instalado e se o daemon está em execução
+    Status,
+}
+
+// Arquivos gerenciados, relativos ao diretório de configuração do usuário
+struct ServiceFiles {
+    unit: PathBuf,
+    autostart: PathBuf,
+}
+
+impl ServiceFiles {
+    // Arquivos no diretório de configuração do usuário ($XDG_CONFIG_HOME, ou ~/.config)
+    fn for_user() -> Result<Self> {
+        let config_dir = dirs::config_dir()
+            .context("Não foi possível encontrar o diretório de configuração do usuário")?;
+        Ok(Self::locate(&config_dir))
+    }
+
+    fn locate(config_dir: &Path) -> Self {
+        Self {
+            unit: config_dir.join("systemd").join("user").join(UNIT_NAME),
+            autostart: config_dir.join("autostart").join(AUTOSTART_NAME),
+        }
+    }
+}
+
+pub fn run(action: ServiceAction) -> Result<()> {
+    if !cfg!(target_os = "linux") {
+        bail!(
+            "O serviço do daemon só é gerenciado pelo acv-inq no Linux; \
+             nos demais sistemas, use scripts/install.sh"
+        );
+    }
+
+    let files = ServiceFiles::for_user()?;
+    let config_path = AppConfig::get_config_path()?;
+    match action {
+        ServiceAction::Install { auto_start } => install(
+            &files,
+            &config_path,
+            &daemon_command()?,
+            systemd_available(),
+            auto_start,
+        ),
+        ServiceAction::Uninstall => uninstall(&files, &config_path, systemd_available()),
+        ServiceAction::Status => status(&files, &config_path),
+    }
+}
+
+// O serviço é sempre instalado, para ser iniciado à mão; a inicialização
+// automática segue `auto_start_daemon`, que só muda com `--auto-start`
+fn install(
+    files: &ServiceFiles,
+    config_path: &Path,
+    command: &[OsString],
+    systemd: bool,
+    enable_auto_start: bool,
+) -> Result<()> {
+    if enable_auto_start {
+        set_auto_start(config_path, true)?;
+    }
+    let auto_start = AppConfig::load_from(config_path)?.auto_start_daemon;
+
+    write_file(&files.unit, &unit_contents(command))?;
+    println!("✅ Serviço systemd criado: {:?}", files.unit);
+    if systemd {
+        systemctl(&["daemon-reload"]);
+    }
+
+    if auto_start {
+        write_file(&files.autostart, &autostart_contents(command, systemd))?;
+        println!("✅ Inicialização automática criada: {:?}", files.autostart);
+        if systemd {
+            systemctl(&["enable", UNIT_NAME]);
+        } else {
+            println!("💡 systemd não encontrado: o daemon será iniciado pela sessão gráfica");
+        }
+    } else {
+        // Uma instalação anterior com auto_start_daemon ativado
+        if files.autostart.exists() {
+            fs::remove_file(&files.autostart)
+                .with_context(|| format!("Falha ao remover {:?}", files.autostart))?;
+            println!("🗑️  Removido: {:?}", files.autostart);
+        }
+        if systemd {
+            systemctl(&["disable", UNIT_NAME]);
+        }
+        println!(
+            "💡 auto_start_daemon está desativado: o daemon não inicia sozinho \
+             (use `acv-inq service install --auto-start` para ativar)"
+        );
+    }
+    if systemd {
+        println!("💡 Para iniciar agora: systemctl --user start {UNIT_NAME}");
+    }
+
+    Ok(())
+}
+
+fn uninstall(files: &ServiceFiles, config_path: &Path, systemd: bool) -> Result<()> {
+    if systemd && files.unit.exists() {
+        systemctl(&["disable", "--now", UNIT_NAME]);
+    }
+
+    for path in [&files.unit, &files.autostart] {
+        if path.exists() {
+            fs::remove_file(path).with_context(|| format!("Falha ao remover {path:?}"))?;
+            println!("🗑️  Removido: {path:?}");
+        }
+    }
+
+    if systemd {
+        systemctl(&["daemon-reload"]);
+    }
+    set_auto_start(config_path, false)?;
+    println!("✅ Inicialização automática do daemon desativada");
+
+    Ok(())
+}
+
+fn status(files: &ServiceFiles, config_path: &Path) -> Result<()> {
+    let config = AppConfig::load_from(config_path)?;
+    let expected = daemon_command().map(|command| format_command(&command))?;
+
+    println!(
+        "🔧 Inicialização automática (auto_start_daemon): {}",
+        if config.auto_start_daemon {
+            "ativada"
+        } else {
+            "desativada"
+        }
+    );
+
+    match fs::read_to_string(&files.unit) {
+        Ok(contents) => {
+            println!("  • Serviço systemd: {:?}", files.unit);
+            let exec = contents
+                .lines()
+                .find_map(|line| line.strip_prefix("ExecStart="));
+            if exec != Some(expected.as_str()) {
+                println!(
+                    "    ⚠️  Aponta para outro executável ou configuração; \
+                     rode `acv-inq service install` para atualizar"
+                );
+            }
+        }
+        Err(_) => println!("  • Serviço systemd: não instalado"),
+    }
+    if files.autostart.exists() {
+        println!("  • Entrada de autostart: {:?}", files.autostart);
+    } else {
+        println!("  • Entrada de autostart: não instalada");
+    }
+
+    // Sem auto_start_daemon, só o serviço pode estar instalado
+    if config.auto_start_daemon != files.autostart.exists()
+        || (files.autostart.exists() && !files.unit.exists())
+    {
+        println!(
+            "⚠️  A configuração e os arquivos instalados não concordam; \
+             rode `acv-inq service install` ou `acv-inq service uninstall`"
+        );
+    }
+
+    if systemd_available() && files.unit.exists() {
+        let state = Command::new("systemctl")
+            .args(["--user", "is-active", UNIT_NAME])
+            .output()
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .unwrap_or_else(|_| "desconhecido".to_string());
+        println!("  • systemctl --user is-active: {state}");
+    }
+
+    match instance::running_daemon()? {
+        Some(holder) => println!("🤖 Daemon em execução ({holder})"),
+        None => println!("💤 Daemon parado"),
+    }
+
+    Ok(())
+}
+
+// Linha de comando do daemon, com caminhos absolutos para `--config` e `--db`
+// (o serviço não roda no diretório atual)
+fn daemon_command() -> Result<Vec<OsString>> {
+    let exe = std::env::current_exe().context("Não foi possível obter caminho do executável")?;
+    let mut command = vec![exe.into_os_string()];
+
+    let options = options::get();
+    for (flag, path) in [("--config", &options.config), ("--db", &options.db)] {
+        if let Some(path) = path {
+            let path = std::path::absolute(path)
+                .with_context(|| format!("Caminho inválido para {flag}: {path:?}"))?;
+            command.push(OsString::from(flag));
+            command.push(path.into_os_string());
+        }
+    }
+
+    command.push(OsString::from("daemon"));
+    Ok(command)
+}
+
+// Junta os argumentos no formato aceito tanto pelo systemd quanto pelas
+// entradas .desktop: aspas duplas quando necessário e `%` duplicado
+fn format_command(command: &[OsString]) -> String {
+    command
+        .iter()
+        .map(|arg| {
+            let arg = arg.to_string_lossy().replace('%', "%%");
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || "\"\\'".contains(c)) {
+                format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+            } else {
+                arg
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn unit_contents(command: &[OsString]) -> String {
+    format!(
+        "[Unit]
+Description=Activity Inquirer - inquéritos automáticos de atividades
+PartOf=graphical-session.target
+After=graphical-session.target
+
+[Service]
+Type=simple
+ExecStart={}
+Restart=on-failure
+RestartSec=10
+
+[Install]
+WantedBy=graphical-session.target
+",
+        format_command(command)
+    )
+}
+
+// Com systemd, a entrada de autostart só inicia o serviço, repassando as
+// variáveis da sessão gráfica; assim nunca há dois daemons. Sem systemd, ela
+// executa o daemon diretamente.
+fn autostart_contents(command: &[OsString], systemd: bool) -> String {
+    let exec = if systemd {
+        format!(
+            "sh -c \"systemctl --user import-environment DISPLAY WAYLAND_DISPLAY XAUTHORITY \
+             && systemctl --user start {UNIT_NAME}\""
+        )
+    } else {
+        format_command(command)
+    };
+
+    format!(
+        "[Desktop Entry]
+Type=Application
+Name=Activity Inquirer Daemon
+Comment=Daemon para inquéritos automáticos de atividades
+Exec={exec}
+Icon=activity-inquirer
+Terminal=false
+Hidden=false
+X-GNOME-Autostart-enabled=true
+StartupNotify=false
+Categories=Utility;
+"
+    )
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Falha ao criar diretório: {parent:?}"))?;
+    }
+    fs::write(path, contents).with_context(|| format!("Falha ao gravar {path:?}"))
+}
+
+fn set_auto_start(config_path: &Path, enabled: bool) -> Result<()> {
+    let mut config = AppConfig::load_from(config_path)?;
+    if config.auto_start_daemon != enabled {
+        config.auto_start_daemon = enabled;
+        config.save_to(config_path)?;
+    }
+    Ok(())
+}
+
+// Mesma verificação do sd_booted(3)
+fn systemd_available() -> bool {
+    Path::new("/run/systemd/system").exists()
+}
+
+// Falhas do systemctl (ex.: sem sessão de usuário) não impedem a instalação dos arquivos
+fn systemctl(args: &[&str]) {
+    match Command::new("systemctl").arg("--user").args(args).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => log::warn!(
+            "⚠️  systemctl --user {} terminou com {status}",
+            args.join(" ")
+        ),
+        Err(e) => log::warn!("⚠️  Falha ao executar systemctl: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TempDir;
+
+    // Diretório de configuração temporário, com os arquivos do serviço e o
+    // config.toml dentro dele
+    struct ConfigHome {
+        dir: TempDir,
+        files: ServiceFiles,
+        config: PathBuf,
+    }
+
+    impl ConfigHome {
+        fn new(name: &str) -> Self {
+            let dir = TempDir::new(&format!("service-{name}"));
+            Self {
+                files: ServiceFiles::locate(dir.path()),
+                config: dir.join("activity-inquirer/config.toml"),
+                dir,
+            }
+        }
+
+        fn auto_start_daemon(&self) -> bool {
+            AppConfig::load_from(&self.config)
+                .unwrap()
+                .auto_start_daemon
+        }
+    }
+
+    fn command() -> Vec<OsString> {
+        [
+            "/opt/acv inq/acv-inq",
+            "--db",
+            "/dados/atividades.db",
+            "daemon",
+        ]
+        .into_iter()
+        .map(OsString::from)
+        .collect()
+    }
+
+    #[test]
+    fn locate_uses_config_dir() {
+        let home = ConfigHome::new("locate");
+
+        assert_eq!(
+            home.files.unit,
+            home.dir.join("systemd/user/activity-inquirer.service")
+        );
+        assert_eq!(
+            home.files.autostart,
+            home.dir.join("autostart/activity-inquirer-daemon.desktop")
+        );
+    }
+
+    #[test]
+    fn format_command_quotes_when_needed() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            format_command(&args(&["/usr/bin/acv-inq", "daemon"])),
+            "/usr/bin/acv-inq daemon"
+        );
+        assert_eq!(
+            format_command(&args(&["/opt/acv inq/acv-inq", ""])),
+            "\"/opt/acv inq/acv-inq\" \"\""
+        );
+        assert_eq!(
+            format_command(&args(&["--config", "/tmp/a \"b\"\\c.toml"])),
+            "--config \"/tmp/a \\\"b\\\"\\\\c.toml\""
+        );
+        assert_eq!(format_command(&args(&["50%"])), "50%%");
+        assert_eq!(format_command(&args(&["it's"])), "\"it's\"");
+    }
+
+    #[test]
+    fn unit_runs_daemon_command() {
+        let unit = unit_contents(&command());
+
+        assert!(
+            unit.contains(
+                "\nExecStart=\"/opt/acv inq/acv-inq\" --db /dados/atividades.db daemon\n"
+            )
+        );
+        assert!(unit.contains("\nType=simple\n"));
+        assert!(unit.contains("\nWantedBy=graphical-session.target\n"));
+    }
+
+    #[test]
+    fn autostart_starts_service_or_daemon() {
+        let with_systemd = autostart_contents(&command(), true);
+        assert!(with_systemd.starts_with("[Desktop Entry]\n"));
+        assert!(with_systemd.contains(&format!("systemctl --user start {UNIT_NAME}\"\n")));
+        assert!(!with_systemd.contains("acv-inq"));
+
+        let without_systemd = autostart_contents(&command(), false);
+        assert!(
+            without_systemd
+                .contains("\nExec=\"/opt/acv inq/acv-inq\" --db /dados/atividades.db daemon\n")
+        );
+    }
+
+    #[test]
+    fn install_and_uninstall_round_trip() {
+        let home = ConfigHome::new("round-trip");
+        let files = &home.files;
+
+        // Sem --auto-start, o serviço é instalado e auto_start_daemon não muda
+        install(files, &home.config, &command(), false, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&files.unit).unwrap(),
+            unit_contents(&command())
+        );
+        assert!(!files.autostart.exists());
+        assert!(!home.auto_start_daemon());
+
+        install(files, &home.config, &command(), false, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(&files.autostart).unwrap(),
+            autostart_contents(&command(), false)
+        );
+        assert!(home.auto_start_daemon());
+
+        status(files, &home.config).unwrap();
+
+        // Reinstalar não desativa o que o usuário ativou
+        install(files, &home.config, &command(), false, false).unwrap();
+        assert!(files.autostart.exists());
+        assert!(home.auto_start_daemon());
+
+        uninstall(files, &home.config, false).unwrap();
+        assert!(!files.unit.exists());
+        assert!(!files.autostart.exists());
+        assert!(!home.auto_start_daemon());
+    }
+
+    #[test]
+    fn install_follows_auto_start_daemon_from_config() {
+        let home = ConfigHome::new("config");
+        let files = &home.files;
+
+        let mut config = AppConfig {
+            auto_start_daemon: true,
+            ..AppConfig::default()
+        };
+        config.save_to(&home.config).unwrap();
+        install(files, &home.config, &command(), false, false).unwrap();
+        assert!(files.autostart.exists());
+
+        // Desativado pelo usuário: a reinstalação remove a entrada de autostart
+        config.auto_start_daemon = false;
+        config.save_to(&home.config).unwrap();
+        install(files, &home.config, &command(), false, false).unwrap();
+        assert!(files.unit.exists());
+        assert!(!files.autostart.exists());
+        assert!(!home.auto_start_daemon());
+    }
+}
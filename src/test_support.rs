@@ -0,0 +1,51 @@
+use std::path::{Path, PathBuf};
+
+use crate::database::Database;
+
+// Diretório temporário exclusivo de um teste, apagado no fim dele. `name`
+// deve ser único entre os testes, que rodam em paralelo no mesmo processo.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("acv-inq-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+// Banco novo, com todas as migrações, num diretório temporário
+pub struct TestDb {
+    pub db: Database,
+    dir: TempDir,
+}
+
+impl TestDb {
+    pub fn new(name: &str) -> Self {
+        let dir = TempDir::new(name);
+        let db = Database::open(&dir.join("activities.db")).unwrap();
+        Self { db, dir }
+    }
+
+    // Caminho do banco, para abrir outra conexão como faria outro processo
+    pub fn path(&self) -> PathBuf {
+        self.dir.join("activities.db")
+    }
+}
@@ -1,12 +1,52 @@
-use crate::database::Database;
+use crate::cli;
 use crate::fonts;
-use crate::models::Activity;
-use anyhow::Result;
-use egui::{Button, CentralPanel, Context, RichText, TextEdit};
+use crate::instance;
+use crate::models::{Activity, ActivityInput, Project};
+use crate::prompter::{InquirySession, Question, WHAT_NOW};
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, Local};
+use egui::{Button, CentralPanel, ComboBox, Context, RichText, ScrollArea, TextEdit};
+use std::time::{Duration as StdDuration, SystemTime};
+
+// Preenchimento de um período sem respostas (ex.: computador suspenso): o
+// usuário registra uma ou mais atividades com o horário em que começaram
+struct Backfill {
+    start: DateTime<Local>,
+    rows: Vec<BackfillRow>,
+}
+
+struct BackfillRow {
+    time: String,
+    description: String,
+}
+
+impl Backfill {
+    fn new(start: DateTime<Local>) -> Self {
+        Self {
+            rows: vec![BackfillRow {
+                time: start.format(Self::time_format(start)).to_string(),
+                description: String::new(),
+            }],
+            start,
+        }
+    }
+
+    // Horários de hoje só com hora e minuto; de outros dias, com a data
+    fn time_format(at: DateTime<Local>) -> &'static str {
+        if at.date_naive() == Local::now().date_naive() {
+            "%H:%M"
+        } else {
+            "%Y-%m-%d %H:%M"
+        }
+    }
+}
 
 pub struct InquiryApp {
-    db: Database,
+    session: InquirySession,
     current_input: String,
+    current_project: Option<String>,
+    current_tags: String,
+    projects: Vec<Project>,
     question_text: String,
     is_first_question: bool,
     last_activity: Option<Activity>,
@@ -15,14 +55,22 @@ pub struct InquiryApp {
     fonts_configured: bool,
     close_requested: bool,
     close_timer: Option<std::time::Instant>,
+    backfill: Option<Backfill>,
+    // Última vez que outro processo pediu para esta janela vir para frente
+    focus_requested_at: Option<SystemTime>,
 }
 
 impl InquiryApp {
-    pub fn new() -> Result<Self> {
-        let db = Database::new()?;
+    // Com `backfill_start`, pergunta o que aconteceu desde esse horário (ver
+    // `InquirySession::backfill_start`) em vez da pergunta normal
+    pub fn new(session: InquirySession, backfill_start: Option<DateTime<Local>>) -> Result<Self> {
+        let projects = session.db().list_projects()?;
         let mut app = InquiryApp {
-            db,
+            session,
             current_input: String::new(),
+            current_project: None,
+            current_tags: String::new(),
+            projects,
             question_text: String::new(),
             is_first_question: true,
             last_activity: None,
@@ -31,6 +79,8 @@ impl InquiryApp {
             fonts_configured: false,
             close_requested: false,
             close_timer: None,
+            backfill: backfill_start.map(Backfill::new),
+            focus_requested_at: instance::inquiry_focus_requested_at(),
         };
 
         app.setup_question()?;
@@ -38,36 +88,40 @@ impl InquiryApp {
     }
 
     fn setup_question(&mut self) -> Result<()> {
-        let count = self.db.count_activities_today()?;
-
-        if count == 0 {
-            self.is_first_question = true;
-            self.question_text = "O que você está fazendo agora?".to_string();
-        } else {
-            self.is_first_question = false;
-            if let Some(last_activity) = self.db.get_last_activity_today()? {
-                self.question_text =
-                    format!("Você ainda está fazendo \"{}\"?", last_activity.description);
+        let question = self.session.question()?;
+        self.question_text = question.text();
+        match question {
+            Question::WhatNow => self.is_first_question = true,
+            Question::StillDoing(last_activity) => {
+                self.is_first_question = false;
                 self.last_activity = Some(last_activity);
-            } else {
-                self.question_text = "O que você está fazendo agora?".to_string();
-                self.is_first_question = true;
             }
         }
 
         Ok(())
     }
 
+    // Junta o texto digitado (com `#tag` e `@projeto`) aos campos de projeto e tags
+    fn build_input(&self) -> ActivityInput {
+        let mut input = ActivityInput::parse(&self.current_input);
+        if input.project.is_none() {
+            input.project = self.current_project.clone();
+        }
+        input.add_tags_from_list(&self.current_tags);
+        input
+    }
+
     fn save_activity(&mut self) -> Result<()> {
-        if self.current_input.trim().is_empty() {
+        let input = self.build_input();
+        if input.description.is_empty() {
             self.message = Some("Por favor, digite uma atividade.".to_string());
             return Ok(());
         }
 
-        self.db
-            .add_activity(self.current_input.trim().to_string())?;
+        self.session.save_new(&input)?;
         self.message = Some("Atividade salva com sucesso!".to_string());
         self.current_input.clear();
+        self.current_tags.clear();
 
         // Fechar a aplicação após salvar
         self.should_close = true;
@@ -75,16 +129,187 @@ impl InquiryApp {
         Ok(())
     }
 
+    fn save_backfill(&mut self) -> Result<()> {
+        let Some(backfill) = &self.backfill else {
+            return Ok(());
+        };
+        let now = Local::now();
+
+        let mut entries = Vec::new();
+        for row in &backfill.rows {
+            if row.description.trim().is_empty() && row.time.trim().is_empty() {
+                continue;
+            }
+
+            let input = ActivityInput::parse(&row.description);
+            if input.description.is_empty() {
+                bail!("Preencha a descrição da atividade das {}", row.time.trim());
+            }
+
+            let at = cli::parse_time(&row.time)?;
+            if at < backfill.start - Duration::minutes(1) || at > now {
+                bail!(
+                    "O horário {} está fora do período ({} a {})",
+                    row.time.trim(),
+                    backfill.start.format("%H:%M"),
+                    now.format("%H:%M")
+                );
+            }
+            entries.push((at, input));
+        }
+
+        if entries.is_empty() {
+            self.message = Some("Registre pelo menos uma atividade.".to_string());
+            return Ok(());
+        }
+
+        let count = entries.len();
+        self.session.save_backfill(entries)?;
+
+        self.message = Some(format!("{count} atividade(s) registrada(s)!"));
+        self.should_close = true;
+        Ok(())
+    }
+
+    fn render_backfill(&mut self, ui: &mut egui::Ui) {
+        let Some(backfill) = &mut self.backfill else {
+            return;
+        };
+
+        ui.label(
+            RichText::new(format!(
+                "Você esteve fora entre {} e {}.",
+                backfill.start.format(Backfill::time_format(backfill.start)),
+                Local::now().format("%H:%M")
+            ))
+            .size(16.0),
+        );
+        ui.label("O que aconteceu nesse período? Informe quando cada atividade começou.");
+        ui.add_space(15.0);
+
+        let mut remove = None;
+        ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+            for (index, row) in backfill.rows.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        TextEdit::singleline(&mut row.time)
+                            .hint_text("HH:MM")
+                            .desired_width(110.0),
+                    );
+                    ui.add(
+                        TextEdit::singleline(&mut row.description)
+                            .hint_text("atividade #tag @projeto")
+                            .desired_width(260.0),
+                    );
+                    if index > 0 && ui.button("󰆴").on_hover_text("Remover").clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+        });
+        if let Some(index) = remove {
+            backfill.rows.remove(index);
+        }
+
+        if ui.button("+ Adicionar atividade").clicked() {
+            backfill.rows.push(BackfillRow {
+                time: String::new(),
+                description: String::new(),
+            });
+        }
+
+        ui.add_space(15.0);
+
+        if ui.add(Button::new("Salvar")).clicked()
+            && let Err(e) = self.save_backfill()
+        {
+            self.message = Some(format!("Erro ao salvar: {e}"));
+        }
+    }
+
+    fn render_question(&mut self, ui: &mut egui::Ui) {
+        // Pergunta
+        ui.label(RichText::new(&self.question_text).size(16.0));
+        ui.add_space(20.0);
+
+        if self.is_first_question {
+            // Campo de texto para nova atividade
+            ui.horizontal(|ui| {
+                ui.label("Atividade:");
+                let response =
+                    ui.add(TextEdit::singleline(&mut self.current_input).desired_width(300.0));
+
+                if response.lost_focus()
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                    && let Err(e) = self.save_activity()
+                {
+                    self.message = Some(format!("Erro ao salvar: {e}"));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Projeto:");
+                ComboBox::from_id_source("project_combo")
+                    .selected_text(self.current_project.as_deref().unwrap_or("Nenhum"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.current_project, None, "Nenhum");
+                        for project in &self.projects {
+                            ui.selectable_value(
+                                &mut self.current_project,
+                                Some(project.name.clone()),
+                                project.label(),
+                            );
+                        }
+                    });
+
+                ui.label("Tags:");
+                ui.add(
+                    TextEdit::singleline(&mut self.current_tags)
+                        .hint_text("foco, reunião")
+                        .desired_width(140.0),
+                );
+            });
+
+            ui.label(
+                RichText::new("Dica: use #tag e @projeto diretamente no texto")
+                    .size(10.0)
+                    .color(egui::Color32::GRAY),
+            );
+
+            ui.add_space(20.0);
+
+            if ui.add(Button::new("Salvar")).clicked()
+                && let Err(e) = self.save_activity()
+            {
+                self.message = Some(format!("Erro ao salvar: {e}"));
+            }
+        } else {
+            // Botões Sim/Não para atividade anterior
+            ui.horizontal(|ui| {
+                if ui.add(Button::new("Sim")).clicked()
+                    && let Err(e) = self.handle_yes_no_response(true)
+                {
+                    self.message = Some(format!("Erro: {e}"));
+                }
+
+                if ui.add(Button::new("Não")).clicked()
+                    && let Err(e) = self.handle_yes_no_response(false)
+                {
+                    self.message = Some(format!("Erro: {e}"));
+                }
+            });
+        }
+    }
+
     fn handle_yes_no_response(&mut self, is_yes: bool) -> Result<()> {
         if is_yes {
-            // Se ainda está fazendo a mesma atividade, salva novamente
-            if let Some(ref last_activity) = self.last_activity {
-                self.db.add_activity(last_activity.description.clone())?;
+            if let Some(id) = self.last_activity.as_ref().and_then(|a| a.id) {
+                self.session.continue_activity(id)?;
                 self.message = Some("Atividade continuada registrada!".to_string());
             }
         } else {
             // Se não está mais fazendo, pergunta o que está fazendo agora
-            self.question_text = "O que você está fazendo agora?".to_string();
+            self.question_text = WHAT_NOW.to_string();
             self.is_first_question = true;
             return Ok(());
         }
@@ -102,6 +327,17 @@ impl eframe::App for InquiryApp {
             self.fonts_configured = true;
         }
 
+        // Outro processo (daemon ou `acv-inq inquiry`) pediu esta janela
+        let focus_requested_at = instance::inquiry_focus_requested_at();
+        if focus_requested_at != self.focus_requested_at {
+            self.focus_requested_at = focus_requested_at;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            ctx.send_viewport_cmd(egui::ViewportCommand::RequestUserAttention(
+                egui::UserAttentionType::Informational,
+            ));
+        }
+        ctx.request_repaint_after(StdDuration::from_millis(500));
+
         CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
                 ui.add_space(50.0);
@@ -110,47 +346,10 @@ impl eframe::App for InquiryApp {
                 ui.label(RichText::new("Activity Inquirer").size(24.0).strong());
                 ui.add_space(30.0);
 
-                // Pergunta
-                ui.label(RichText::new(&self.question_text).size(16.0));
-                ui.add_space(20.0);
-
-                if self.is_first_question {
-                    // Campo de texto para nova atividade
-                    ui.horizontal(|ui| {
-                        ui.label("Atividade:");
-                        let response = ui.add(
-                            TextEdit::singleline(&mut self.current_input).desired_width(300.0),
-                        );
-
-                        if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                            if let Err(e) = self.save_activity() {
-                                self.message = Some(format!("Erro ao salvar: {e}"));
-                            }
-                        }
-                    });
-
-                    ui.add_space(20.0);
-
-                    if ui.add(Button::new("Salvar")).clicked() {
-                        if let Err(e) = self.save_activity() {
-                            self.message = Some(format!("Erro ao salvar: {e}"));
-                        }
-                    }
+                if self.backfill.is_some() {
+                    self.render_backfill(ui);
                 } else {
-                    // Botões Sim/Não para atividade anterior
-                    ui.horizontal(|ui| {
-                        if ui.add(Button::new("Sim")).clicked() {
-                            if let Err(e) = self.handle_yes_no_response(true) {
-                                self.message = Some(format!("Erro: {e}"));
-                            }
-                        }
-
-                        if ui.add(Button::new("Não")).clicked() {
-                            if let Err(e) = self.handle_yes_no_response(false) {
-                                self.message = Some(format!("Erro: {e}"));
-                            }
-                        }
-                    });
+                    self.render_question(ui);
                 }
 
                 ui.add_space(20.0);
@@ -179,11 +378,11 @@ impl eframe::App for InquiryApp {
                 ctx.request_repaint();
 
                 // Se passou mais de 2 segundos tentando fechar, forçar saída
-                if let Some(timer) = self.close_timer {
-                    if timer.elapsed().as_secs() > 2 {
-                        eprintln!("⚠️  Forçando fechamento da aplicação após timeout");
-                        std::process::exit(0);
-                    }
+                if let Some(timer) = self.close_timer
+                    && timer.elapsed().as_secs() > 2
+                {
+                    log::warn!("⚠️  Forçando fechamento da aplicação após timeout");
+                    std::process::exit(self.session.report().finish());
                 }
             }
         }
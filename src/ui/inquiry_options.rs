@@ -0,0 +1,90 @@
+use egui::{ComboBox, DragValue, TextEdit};
+
+use crate::config::{AppConfig, PrompterConfig, PrompterKind, UnansweredPolicy};
+
+// Opções do inquérito (tempo limite, política sem resposta e forma de
+// perguntar), usadas pela tela de configurações e pela página de configurações
+// do visualizador
+pub struct InquiryOptionsEditor {
+    timeout_minutes: u64,
+    unanswered_policy: UnansweredPolicy,
+    prompter: PrompterConfig,
+}
+
+impl InquiryOptionsEditor {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            timeout_minutes: config.inquiry_timeout_minutes,
+            unanswered_policy: config.unanswered_policy,
+            prompter: config.prompter.clone(),
+        }
+    }
+
+    pub fn apply(&self, config: &mut AppConfig) {
+        config.inquiry_timeout_minutes = self.timeout_minutes;
+        config.unanswered_policy = self.unanswered_policy;
+        config.prompter = self.prompter.clone();
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Fechar inquérito sem resposta após:");
+            ui.add(
+                DragValue::new(&mut self.timeout_minutes)
+                    .clamp_range(1..=120)
+                    .suffix(" min"),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Quando não houver resposta:");
+            ComboBox::from_id_source("unanswered_policy_combo")
+                .selected_text(self.unanswered_policy.label())
+                .show_ui(ui, |ui| {
+                    for policy in [
+                        UnansweredPolicy::Nothing,
+                        UnansweredPolicy::Away,
+                        UnansweredPolicy::RepeatLast,
+                    ] {
+                        ui.selectable_value(&mut self.unanswered_policy, policy, policy.label());
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Perguntar por:");
+            ComboBox::from_id_source("prompter_kind_combo")
+                .selected_text(self.prompter.kind.label())
+                .show_ui(ui, |ui| {
+                    for kind in [
+                        PrompterKind::Gui,
+                        PrompterKind::Terminal,
+                        PrompterKind::Command,
+                    ] {
+                        ui.selectable_value(&mut self.prompter.kind, kind, kind.label());
+                    }
+                });
+        });
+        match self.prompter.kind {
+            PrompterKind::Gui => {}
+            PrompterKind::Terminal => {
+                ui.horizontal(|ui| {
+                    ui.label("Terminal:");
+                    ui.add(
+                        TextEdit::singleline(&mut self.prompter.tty)
+                            .hint_text("/dev/tty")
+                            .desired_width(200.0),
+                    );
+                });
+            }
+            PrompterKind::Command => {
+                ui.horizontal(|ui| {
+                    ui.label("Comando:");
+                    ui.add(
+                        TextEdit::singleline(&mut self.prompter.command)
+                            .hint_text("rofi -dmenu -p \"$ACV_QUESTION\"")
+                            .desired_width(260.0),
+                    );
+                });
+            }
+        }
+    }
+}
@@ -1,4 +1,6 @@
 pub mod inquiry;
+pub mod inquiry_options;
+pub mod schedule_editor;
 pub mod settings;
 pub mod theme;
 pub mod viewer;
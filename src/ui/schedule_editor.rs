@@ -0,0 +1,233 @@
+use anyhow::{Result, anyhow};
+use chrono::{NaiveDate, Weekday};
+use egui::{ComboBox, DragValue, Grid, RichText, TextEdit};
+
+use crate::config::{ScheduleConfig, ScheduleMode, TimeWindow};
+
+const WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "Segunda"),
+    (Weekday::Tue, "Terça"),
+    (Weekday::Wed, "Quarta"),
+    (Weekday::Thu, "Quinta"),
+    (Weekday::Fri, "Sexta"),
+    (Weekday::Sat, "Sábado"),
+    (Weekday::Sun, "Domingo"),
+];
+
+// Formulário do horário de trabalho, usado pela tela de configurações e pela
+// página de configurações do visualizador. Os campos são texto livre e só são
+// validados ao salvar.
+pub struct ScheduleEditor {
+    mode: ScheduleMode,
+    cron: String,
+    samples_per_window: u32,
+    min_gap_minutes: u64,
+    enabled: bool,
+    days: [String; 7],
+    lunch: String,
+    skip_dates: String,
+}
+
+impl ScheduleEditor {
+    pub fn new(schedule: &ScheduleConfig) -> Self {
+        Self {
+            mode: schedule.mode,
+            cron: schedule.cron.clone(),
+            samples_per_window: schedule.samples_per_window,
+            min_gap_minutes: schedule.min_gap_minutes,
+            enabled: schedule.enabled,
+            days: WEEKDAYS.map(|(weekday, _)| format_windows(schedule.windows(weekday))),
+            lunch: schedule
+                .lunch
+                .map(|lunch| lunch.to_string())
+                .unwrap_or_default(),
+            skip_dates: schedule
+                .skip_dates
+                .iter()
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    pub fn to_config(&self) -> Result<ScheduleConfig> {
+        let mut schedule = ScheduleConfig {
+            mode: self.mode,
+            cron: self.cron.trim().to_string(),
+            samples_per_window: self.samples_per_window,
+            min_gap_minutes: self.min_gap_minutes,
+            enabled: self.enabled,
+            ..ScheduleConfig::default()
+        };
+        if schedule.mode == ScheduleMode::Cron {
+            schedule.parse_cron()?;
+        }
+
+        for ((weekday, label), text) in WEEKDAYS.iter().zip(&self.days) {
+            *schedule.windows_mut(*weekday) = text
+                .split(',')
+                .map(str::trim)
+                .filter(|window| !window.is_empty())
+                .map(TimeWindow::parse)
+                .collect::<Result<_>>()
+                .map_err(|e| anyhow!("{label}: {e}"))?;
+        }
+
+        let lunch = self.lunch.trim();
+        schedule.lunch = if lunch.is_empty() {
+            None
+        } else {
+            Some(TimeWindow::parse(lunch).map_err(|e| anyhow!("Almoço: {e}"))?)
+        };
+
+        let mut skip_dates = self
+            .skip_dates
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|date| !date.is_empty())
+            .map(|date| {
+                NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                    .map_err(|_| anyhow!("Data ignorada inválida: \"{date}\". Use AAAA-MM-DD"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        skip_dates.sort();
+        skip_dates.dedup();
+        schedule.skip_dates = skip_dates;
+
+        Ok(schedule)
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(RichText::new("󰃰 Agendamento").size(16.0).strong());
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Modo:");
+                    ComboBox::from_id_source("schedule_mode_combo")
+                        .selected_text(self.mode.label())
+                        .show_ui(ui, |ui| {
+                            for mode in [
+                                ScheduleMode::Interval,
+                                ScheduleMode::Aligned,
+                                ScheduleMode::Cron,
+                                ScheduleMode::Sampling,
+                            ] {
+                                ui.selectable_value(&mut self.mode, mode, mode.label());
+                            }
+                        });
+                });
+
+                ui.add_space(5.0);
+                match self.mode {
+                    ScheduleMode::Interval => {
+                        ui.label(
+                            "Pergunta a cada intervalo, contado a partir do último inquérito.",
+                        );
+                    }
+                    ScheduleMode::Aligned => {
+                        ui.label("Pergunta em horários redondos: com 30 minutos, às :00 e às :30.");
+                    }
+                    ScheduleMode::Cron => {
+                        ui.horizontal(|ui| {
+                            ui.label("Expressão:");
+                            ui.add(
+                                TextEdit::singleline(&mut self.cron)
+                                    .hint_text("*/30 9-17 * * 1-5")
+                                    .desired_width(220.0),
+                            );
+                        });
+                        ui.label(
+                            RichText::new("minuto hora dia mês dia-da-semana")
+                                .small()
+                                .weak(),
+                        );
+                    }
+                    ScheduleMode::Sampling => {
+                        ui.label(
+                            "Sorteia horários dentro de cada janela do horário de trabalho \
+                             (que precisa estar ativo), para evitar viés de horários fixos.",
+                        );
+                        ui.horizontal(|ui| {
+                            ui.label("Inquéritos por janela:");
+                            ui.add(
+                                DragValue::new(&mut self.samples_per_window).clamp_range(1..=20),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Distância mínima (minutos):");
+                            ui.add(DragValue::new(&mut self.min_gap_minutes).clamp_range(0..=240));
+                        });
+                    }
+                }
+            });
+        });
+
+        ui.add_space(20.0);
+
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(RichText::new(" Horário de Trabalho").size(16.0).strong());
+                ui.add_space(10.0);
+
+                ui.checkbox(
+                    &mut self.enabled,
+                    "Perguntar apenas dentro do horário de trabalho",
+                );
+                ui.add_space(5.0);
+
+                ui.add_enabled_ui(self.enabled, |ui| {
+                    Grid::new("schedule_grid")
+                        .num_columns(2)
+                        .spacing([10.0, 6.0])
+                        .show(ui, |ui| {
+                            for ((_, label), text) in WEEKDAYS.iter().zip(&mut self.days) {
+                                ui.label(*label);
+                                ui.add(
+                                    TextEdit::singleline(text)
+                                        .hint_text("folga")
+                                        .desired_width(220.0),
+                                );
+                                ui.end_row();
+                            }
+
+                            ui.label("Almoço");
+                            ui.add(
+                                TextEdit::singleline(&mut self.lunch)
+                                    .hint_text("sem pausa")
+                                    .desired_width(220.0),
+                            );
+                            ui.end_row();
+                        });
+
+                    ui.add_space(5.0);
+                    ui.label("Datas sem inquéritos (feriados, férias), uma por linha:");
+                    ui.add(
+                        TextEdit::multiline(&mut self.skip_dates)
+                            .hint_text("2024-12-25")
+                            .desired_rows(3)
+                            .desired_width(220.0),
+                    );
+
+                    ui.add_space(5.0);
+                    ui.label(
+                        RichText::new(
+                            "Use HH:MM-HH:MM e separe várias janelas por vírgula. \
+                             Dias vazios ficam sem inquéritos.",
+                        )
+                        .small()
+                        .weak(),
+                    );
+                });
+            });
+        });
+    }
+}
+
+fn format_windows(windows: &[TimeWindow]) -> String {
+    windows
+        .iter()
+        .map(TimeWindow::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
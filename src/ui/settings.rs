@@ -1,14 +1,18 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, ScheduleConfig};
 use crate::models::Theme;
+use crate::ui::inquiry_options::InquiryOptionsEditor;
+use crate::ui::schedule_editor::ScheduleEditor;
 use crate::ui::theme;
 use anyhow::Result;
-use egui::{Button, CentralPanel, ComboBox, Context, RichText};
+use egui::{Button, CentralPanel, ComboBox, Context, RichText, ScrollArea};
 
 pub struct SettingsApp {
     config: AppConfig,
     current_theme: Theme,
     message: Option<String>,
     selected_interval: u64,
+    schedule_editor: ScheduleEditor,
+    inquiry_options: InquiryOptionsEditor,
 }
 
 impl SettingsApp {
@@ -21,6 +25,8 @@ impl SettingsApp {
 
         Ok(SettingsApp {
             selected_interval: config.daemon_interval_minutes,
+            schedule_editor: ScheduleEditor::new(&config.schedule),
+            inquiry_options: InquiryOptionsEditor::new(&config),
             config,
             current_theme,
             message: None,
@@ -28,12 +34,15 @@ impl SettingsApp {
     }
 
     fn save_config(&mut self) -> Result<()> {
+        self.config.schedule = self.schedule_editor.to_config()?;
+        self.inquiry_options.apply(&mut self.config);
         self.config.daemon_interval_minutes = self.selected_interval;
         self.config.theme = match self.current_theme {
             Theme::GruvboxDark => "GruvboxDark".to_string(),
             Theme::GruvboxLight => "GruvboxLight".to_string(),
         };
 
+        self.config.validate()?;
         self.config.save()?;
         self.message = Some(" Configurações salvas com sucesso!".to_string());
         Ok(())
@@ -51,7 +60,7 @@ impl eframe::App for SettingsApp {
         theme::apply_theme(ctx, self.current_theme);
 
         CentralPanel::default().show(ctx, |ui| {
-            ui.vertical(|ui| {
+            ScrollArea::vertical().show(ui, |ui| {
                 // Cabeçalho
                 ui.horizontal(|ui| {
                     ui.label(
@@ -119,11 +128,18 @@ impl eframe::App for SettingsApp {
                                 .color(egui::Color32::from_rgb(255, 165, 0)),
                             );
                         }
+
+                        ui.add_space(10.0);
+                        self.inquiry_options.show(ui);
                     });
                 });
 
                 ui.add_space(20.0);
 
+                self.schedule_editor.show(ui);
+
+                ui.add_space(20.0);
+
                 // Configurações de Tema
                 ui.group(|ui| {
                     ui.vertical(|ui| {
@@ -171,10 +187,9 @@ impl eframe::App for SettingsApp {
                     if ui
                         .add(Button::new("󰆓 Salvar Configurações").min_size([150.0, 30.0].into()))
                         .clicked()
+                        && let Err(e) = self.save_config()
                     {
-                        if let Err(e) = self.save_config() {
-                            self.message = Some(format!("❌ Erro ao salvar: {e}"));
-                        }
+                        self.message = Some(format!("❌ Erro ao salvar: {e}"));
                     }
 
                     ui.add_space(10.0);
@@ -185,6 +200,8 @@ impl eframe::App for SettingsApp {
                     {
                         let default_config = AppConfig::default();
                         self.selected_interval = default_config.daemon_interval_minutes;
+                        self.inquiry_options = InquiryOptionsEditor::new(&default_config);
+                        self.schedule_editor = ScheduleEditor::new(&ScheduleConfig::default());
                         self.current_theme = Theme::GruvboxDark;
                         self.change_theme(ctx, Theme::GruvboxDark);
                         self.message =
@@ -204,7 +221,7 @@ impl eframe::App for SettingsApp {
                         ui.add_space(5.0);
 
                         ui.label("• As configurações são salvas automaticamente");
-                        ui.label("• O daemon precisa ser reiniciado para aplicar novos intervalos");
+                        ui.label("• O daemon em execução aplica as alterações em poucos segundos");
                         ui.label("• Intervalo mínimo: 1 minuto");
                         ui.label(
                             "• Configurações ficam em: ~/.config/activity-inquirer/config.toml",
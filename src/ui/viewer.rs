@@ -1,26 +1,100 @@
-use crate::config::AppConfig;
+use crate::config::{AppConfig, ScheduleConfig};
 use crate::database::Database;
 use crate::fonts;
-use crate::models::{Activity, AppPage, Theme};
+use crate::models::{
+    Activity, ActivityInput, AppPage, DailySummary, DateRange, DescriptionTotal, Project,
+    ResponseCounts, SearchResult, Theme, format_duration,
+};
+use crate::ui::inquiry_options::InquiryOptionsEditor;
+use crate::ui::schedule_editor::ScheduleEditor;
 use crate::ui::theme;
 use anyhow::Result;
-use chrono::{Local, NaiveDate};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
 use egui::{CentralPanel, ComboBox, Context, RichText, ScrollArea};
 
 pub struct ViewerApp {
     db: Database,
     selected_date: NaiveDate,
     activities: Vec<Activity>,
+    // Projetos, tags e filtros
+    projects: Vec<Project>,
+    tags: Vec<String>,
+    project_filter: Option<String>,
+    tag_filter: Option<String>,
+    // Busca textual
+    search_query: String,
+    search_results: Vec<SearchResult>,
+    // Estatísticas por período
+    stats_period: StatsPeriod,
+    stats: Option<PeriodStats>,
     current_theme: Theme,
     message: Option<String>,
     current_page: AppPage,
     // Configurações
     config: AppConfig,
     selected_interval: u64,
+    schedule_editor: ScheduleEditor,
+    inquiry_options: InquiryOptionsEditor,
     // Controle de fontes
     fonts_configured: bool,
+    // Edição e remoção de atividades
+    editing: Option<ActivityEdit>,
+    pending_delete: Option<i64>,
 }
 
+struct ActivityEdit {
+    id: i64,
+    description: String,
+    project: String,
+    tags: String,
+    timestamp: String,
+    end_timestamp: String,
+}
+
+enum ActivityAction {
+    StartEdit(Activity),
+    CancelEdit,
+    SaveEdit,
+    RequestDelete(i64),
+    CancelDelete,
+    Delete(i64),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StatsPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl StatsPeriod {
+    fn label(self) -> &'static str {
+        match self {
+            StatsPeriod::Day => "Dia",
+            StatsPeriod::Week => "Semana",
+            StatsPeriod::Month => "Mês",
+        }
+    }
+
+    fn range_for(self, date: NaiveDate) -> DateRange {
+        match self {
+            StatsPeriod::Day => DateRange::day(date),
+            StatsPeriod::Week => DateRange::week_of(date),
+            StatsPeriod::Month => DateRange::month_of(date),
+        }
+    }
+}
+
+struct PeriodStats {
+    range: DateRange,
+    totals: Vec<DescriptionTotal>,
+    days: Vec<DailySummary>,
+    responses: ResponseCounts,
+}
+
+const EDIT_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
+const SEARCH_LIMIT: usize = 200;
+
 impl ViewerApp {
     pub fn new() -> Result<Self> {
         let db = Database::new()?;
@@ -36,12 +110,24 @@ impl ViewerApp {
             db,
             selected_date,
             activities: Vec::new(),
+            projects: Vec::new(),
+            tags: Vec::new(),
+            project_filter: None,
+            tag_filter: None,
+            search_query: String::new(),
+            search_results: Vec::new(),
+            stats_period: StatsPeriod::Week,
+            stats: None,
             current_theme,
             message: None,
             current_page: AppPage::Viewer,
             selected_interval: config.daemon_interval_minutes,
+            schedule_editor: ScheduleEditor::new(&config.schedule),
+            inquiry_options: InquiryOptionsEditor::new(&config),
             config,
             fonts_configured: false,
+            editing: None,
+            pending_delete: None,
         };
 
         app.load_activities()?;
@@ -49,6 +135,9 @@ impl ViewerApp {
     }
 
     fn load_activities(&mut self) -> Result<()> {
+        self.editing = None;
+        self.pending_delete = None;
+
         match self.db.get_activities_for_date(self.selected_date) {
             Ok(activities) => {
                 self.activities = activities;
@@ -59,25 +148,173 @@ impl ViewerApp {
                 self.activities.clear();
             }
         }
+
+        self.projects = self.db.list_projects()?;
+        self.tags = self.db.list_tags()?;
+        self.stats = None;
+        Ok(())
+    }
+
+    fn load_stats(&mut self) -> Result<()> {
+        let range = self.stats_period.range_for(self.selected_date);
+        self.stats = Some(PeriodStats {
+            range,
+            totals: self.db.time_per_description(range)?,
+            days: self.db.daily_summaries(range)?,
+            responses: self.db.response_counts(range)?,
+        });
         Ok(())
     }
 
-    fn format_time(&self, activity: &Activity) -> String {
+    fn matches_filters(
+        activity: &Activity,
+        project_filter: &Option<String>,
+        tag_filter: &Option<String>,
+    ) -> bool {
+        let project_matches = project_filter.as_ref().is_none_or(|name| {
+            activity
+                .project
+                .as_ref()
+                .is_some_and(|project| project.name.eq_ignore_ascii_case(name))
+        });
+        let tag_matches = tag_filter
+            .as_ref()
+            .is_none_or(|tag| activity.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+
+        project_matches && tag_matches
+    }
+
+    fn run_search(&mut self) {
+        match self.db.search(&self.search_query, SEARCH_LIMIT) {
+            Ok(results) => self.search_results = results,
+            Err(e) => {
+                self.search_results.clear();
+                self.message = Some(format!("Erro na busca: {e}"));
+            }
+        }
+    }
+
+    fn open_date(&mut self, date: NaiveDate) {
+        self.selected_date = date;
+        self.current_page = AppPage::Viewer;
+        if let Err(e) = self.load_activities() {
+            self.message = Some(format!("Erro ao carregar atividades: {e}"));
+        }
+    }
+
+    fn save_projects(&mut self) -> Result<()> {
+        for project in &self.projects {
+            self.db.update_project(project)?;
+        }
+        self.load_activities()?;
+        self.message = Some(" Projetos salvos".to_string());
+        Ok(())
+    }
+
+    fn format_time(activity: &Activity) -> String {
         activity.timestamp.format("%H:%M").to_string()
     }
 
+    fn parse_edit_timestamp(text: &str) -> Result<DateTime<Local>> {
+        let naive = NaiveDateTime::parse_from_str(text.trim(), EDIT_TIMESTAMP_FORMAT)
+            .map_err(|_| anyhow::anyhow!("Horário inválido, use o formato AAAA-MM-DD HH:MM"))?;
+
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(|| anyhow::anyhow!("Horário inexistente no fuso horário local"))
+    }
+
+    fn save_edit(&mut self) -> Result<()> {
+        let Some(edit) = self.editing.as_ref() else {
+            return Ok(());
+        };
+
+        let mut input = ActivityInput {
+            description: edit.description.trim().to_string(),
+            project: Some(edit.project.trim().to_string()).filter(|p| !p.is_empty()),
+            tags: Vec::new(),
+        };
+        input.add_tags_from_list(&edit.tags);
+        if input.description.is_empty() {
+            anyhow::bail!("A descrição não pode ficar vazia");
+        }
+        let timestamp = Self::parse_edit_timestamp(&edit.timestamp)?;
+        let end_timestamp = Self::parse_edit_timestamp(&edit.end_timestamp)?;
+
+        self.db
+            .update_activity(edit.id, &input, timestamp, end_timestamp)?;
+        self.editing = None;
+        self.load_activities()?;
+        self.message = Some(" Atividade atualizada".to_string());
+        Ok(())
+    }
+
+    fn handle_activity_action(&mut self, action: ActivityAction) {
+        let result = match action {
+            ActivityAction::StartEdit(activity) => {
+                self.pending_delete = None;
+                self.editing = activity.id.map(|id| ActivityEdit {
+                    id,
+                    description: activity.description.clone(),
+                    project: activity
+                        .project
+                        .as_ref()
+                        .map(|project| project.name.clone())
+                        .unwrap_or_default(),
+                    tags: activity.tags.join(", "),
+                    timestamp: activity.timestamp.format(EDIT_TIMESTAMP_FORMAT).to_string(),
+                    end_timestamp: activity
+                        .end_timestamp
+                        .format(EDIT_TIMESTAMP_FORMAT)
+                        .to_string(),
+                });
+                Ok(())
+            }
+            ActivityAction::CancelEdit => {
+                self.editing = None;
+                Ok(())
+            }
+            ActivityAction::SaveEdit => self.save_edit(),
+            ActivityAction::RequestDelete(id) => {
+                self.editing = None;
+                self.pending_delete = Some(id);
+                Ok(())
+            }
+            ActivityAction::CancelDelete => {
+                self.pending_delete = None;
+                Ok(())
+            }
+            ActivityAction::Delete(id) => {
+                self.pending_delete = None;
+                self.db.delete_activity(id).and_then(|_| {
+                    self.load_activities()?;
+                    self.message = Some(" Atividade removida".to_string());
+                    Ok(())
+                })
+            }
+        };
+
+        if let Err(e) = result {
+            self.message = Some(format!("Erro: {e}"));
+        }
+    }
+
     fn change_theme(&mut self, ctx: &Context, new_theme: Theme) {
         self.current_theme = new_theme;
         theme::apply_theme(ctx, new_theme);
     }
 
     fn save_config(&mut self) -> Result<()> {
+        self.config.schedule = self.schedule_editor.to_config()?;
+        self.inquiry_options.apply(&mut self.config);
         self.config.daemon_interval_minutes = self.selected_interval;
         self.config.theme = match self.current_theme {
             Theme::GruvboxDark => "GruvboxDark".to_string(),
             Theme::GruvboxLight => "GruvboxLight".to_string(),
         };
 
+        self.config.validate()?;
         self.config.save()?;
         self.message = Some(" Configurações salvas com sucesso!".to_string());
         Ok(())
@@ -105,6 +342,20 @@ impl eframe::App for ViewerApp {
                     self.current_page = AppPage::Viewer;
                 }
 
+                if ui
+                    .selectable_label(self.current_page == AppPage::Search, "Busca")
+                    .clicked()
+                {
+                    self.current_page = AppPage::Search;
+                }
+
+                if ui
+                    .selectable_label(self.current_page == AppPage::Stats, "Estatísticas")
+                    .clicked()
+                {
+                    self.current_page = AppPage::Stats;
+                }
+
                 if ui
                     .selectable_label(self.current_page == AppPage::Settings, "Configurações")
                     .clicked()
@@ -118,6 +369,8 @@ impl eframe::App for ViewerApp {
             // Renderizar conteúdo baseado na página atual
             match self.current_page {
                 AppPage::Viewer => self.render_viewer_content(ctx, ui),
+                AppPage::Search => self.render_search_content(ui),
+                AppPage::Stats => self.render_stats_content(ui),
                 AppPage::Settings => self.render_settings_content(ctx, ui),
             }
         });
@@ -175,12 +428,12 @@ impl ViewerApp {
                 ui.label("Data:");
 
                 let mut date_string = self.selected_date.format("%Y-%m-%d").to_string();
-                if ui.text_edit_singleline(&mut date_string).changed() {
-                    if let Ok(new_date) = NaiveDate::parse_from_str(&date_string, "%Y-%m-%d") {
-                        self.selected_date = new_date;
-                        if let Err(e) = self.load_activities() {
-                            self.message = Some(format!("Erro ao carregar atividades: {e}"));
-                        }
+                if ui.text_edit_singleline(&mut date_string).changed()
+                    && let Ok(new_date) = NaiveDate::parse_from_str(&date_string, "%Y-%m-%d")
+                {
+                    self.selected_date = new_date;
+                    if let Err(e) = self.load_activities() {
+                        self.message = Some(format!("Erro ao carregar atividades: {e}"));
                     }
                 }
 
@@ -199,6 +452,37 @@ impl ViewerApp {
                 }
             });
 
+            // Filtros por projeto e tag
+            ui.horizontal(|ui| {
+                ui.label("Projeto:");
+                ComboBox::from_id_source("project_filter")
+                    .selected_text(self.project_filter.as_deref().unwrap_or("Todos"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.project_filter, None, "Todos");
+                        for project in &self.projects {
+                            ui.selectable_value(
+                                &mut self.project_filter,
+                                Some(project.name.clone()),
+                                project.label(),
+                            );
+                        }
+                    });
+
+                ui.label("Tag:");
+                ComboBox::from_id_source("tag_filter")
+                    .selected_text(self.tag_filter.as_deref().unwrap_or("Todas"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.tag_filter, None, "Todas");
+                        for tag in &self.tags {
+                            ui.selectable_value(
+                                &mut self.tag_filter,
+                                Some(tag.clone()),
+                                format!("#{tag}"),
+                            );
+                        }
+                    });
+            });
+
             ui.add_space(10.0);
 
             // Mensagem de erro/status
@@ -225,18 +509,43 @@ impl ViewerApp {
             );
             ui.add_space(10.0);
 
+            let visible: Vec<Activity> = self
+                .activities
+                .iter()
+                .filter(|a| Self::matches_filters(a, &self.project_filter, &self.tag_filter))
+                .cloned()
+                .collect();
+
             if self.activities.is_empty() {
                 ui.label("Nenhuma atividade registrada para este dia.");
+            } else if visible.is_empty() {
+                ui.label("Nenhuma atividade corresponde aos filtros selecionados.");
             } else {
+                let mut action = None;
+
                 ScrollArea::vertical().show(ui, |ui| {
-                    for (index, activity) in self.activities.iter().enumerate() {
+                    for activity in &visible {
                         // Criar um grupo visual para cada atividade
                         ui.group(|ui| {
+                            let is_editing = self
+                                .editing
+                                .as_ref()
+                                .is_some_and(|edit| Some(edit.id) == activity.id);
+
+                            if is_editing {
+                                if let Some(edit) = self.editing.as_mut()
+                                    && let Some(edit_action) = Self::render_edit_form(ui, edit)
+                                {
+                                    action = Some(edit_action);
+                                }
+                                return;
+                            }
+
                             ui.horizontal(|ui| {
                                 // Horário em destaque
                                 ui.vertical(|ui| {
                                     ui.label(
-                                        RichText::new(self.format_time(activity))
+                                        RichText::new(Self::format_time(activity))
                                             .monospace()
                                             .strong()
                                             .size(14.0)
@@ -260,73 +569,369 @@ impl ViewerApp {
 
                                 // Descrição da atividade
                                 ui.vertical(|ui| {
-                                    ui.label(RichText::new(&activity.description).size(13.0));
-
-                                    // Mostrar tempo desde a atividade anterior (se houver)
-                                    if index > 0 {
-                                        let prev_activity = &self.activities[index - 1];
-                                        let duration = activity
-                                            .timestamp
-                                            .signed_duration_since(prev_activity.timestamp);
-
-                                        if let Ok(duration_std) = duration.to_std() {
-                                            let minutes = duration_std.as_secs() / 60;
-                                            if minutes > 0 {
-                                                let time_diff = if minutes < 60 {
-                                                    format!("{minutes}min depois")
-                                                } else {
-                                                    let hours = minutes / 60;
-                                                    let remaining_minutes = minutes % 60;
-                                                    if remaining_minutes == 0 {
-                                                        format!("{hours}h depois")
-                                                    } else {
-                                                        format!(
-                                                            "{hours}h{remaining_minutes}m depois"
-                                                        )
-                                                    }
-                                                };
-                                                println!(
-                                                    "Tempo desde a última atividade: {time_diff}"
+                                    // Registros automáticos de inquéritos sem resposta
+                                    // aparecem apagados, com a origem indicada
+                                    if activity.kind.is_automatic() {
+                                        ui.label(
+                                            RichText::new(&activity.description)
+                                                .size(13.0)
+                                                .italics()
+                                                .color(egui::Color32::GRAY),
+                                        );
+                                        ui.label(
+                                            RichText::new(format!(
+                                                "Inquérito sem resposta: {}",
+                                                activity.kind.label()
+                                            ))
+                                            .size(10.0)
+                                            .color(egui::Color32::from_rgb(254, 128, 25)),
+                                        ); // Gruvbox orange
+                                    } else {
+                                        ui.label(RichText::new(&activity.description).size(13.0));
+                                    }
+
+                                    // Projeto e tags
+                                    if activity.project.is_some() || !activity.tags.is_empty() {
+                                        ui.horizontal_wrapped(|ui| {
+                                            if let Some(project) = &activity.project {
+                                                let color = project
+                                                    .rgb()
+                                                    .map(|[r, g, b]| {
+                                                        egui::Color32::from_rgb(r, g, b)
+                                                    })
+                                                    .unwrap_or(egui::Color32::GRAY);
+                                                ui.label(
+                                                    RichText::new(project.label())
+                                                        .size(11.0)
+                                                        .strong()
+                                                        .color(color),
                                                 );
-                                                let time_label = format!("󱑆 {time_diff}");
-                                                println!("time_label: {time_label}");
+                                            }
+                                            for tag in &activity.tags {
                                                 ui.label(
-                                                    RichText::new(time_label)
-                                                        .size(10.0)
+                                                    RichText::new(format!("#{tag}"))
+                                                        .size(11.0)
                                                         .color(egui::Color32::GRAY),
                                                 );
                                             }
-                                        }
+                                        });
                                     }
+
+                                    // Duração do intervalo
+                                    let time_label = format!(
+                                        "󱑆 até {} · {}",
+                                        activity.end_timestamp.format("%H:%M"),
+                                        format_duration(activity.duration())
+                                    );
+                                    ui.label(
+                                        RichText::new(time_label)
+                                            .size(10.0)
+                                            .color(egui::Color32::GRAY),
+                                    );
                                 });
+
+                                // Ações de edição e remoção
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        let Some(id) = activity.id else {
+                                            return;
+                                        };
+
+                                        if self.pending_delete == Some(id) {
+                                            if ui.button("Cancelar").clicked() {
+                                                action = Some(ActivityAction::CancelDelete);
+                                            }
+                                            if ui
+                                                .button(
+                                                    RichText::new("Confirmar remoção")
+                                                        .color(egui::Color32::RED),
+                                                )
+                                                .clicked()
+                                            {
+                                                action = Some(ActivityAction::Delete(id));
+                                            }
+                                        } else {
+                                            if ui.button("󰆴 Remover").clicked() {
+                                                action = Some(ActivityAction::RequestDelete(id));
+                                            }
+                                            if ui.button("󰏫 Editar").clicked() {
+                                                action = Some(ActivityAction::StartEdit(
+                                                    activity.clone(),
+                                                ));
+                                            }
+                                        }
+                                    },
+                                );
                             });
                         });
 
                         ui.add_space(8.0);
                     }
                 });
+
+                if let Some(action) = action {
+                    self.handle_activity_action(action);
+                }
             }
 
             ui.add_space(20.0);
 
             // Estatísticas básicas
-            if !self.activities.is_empty() {
+            if !visible.is_empty() {
                 ui.separator();
                 ui.add_space(10.0);
                 ui.label(RichText::new("Resumo").size(14.0).strong());
-                ui.label(format!("Total de registros: {}", self.activities.len()));
+                ui.label(format!("Total de registros: {}", visible.len()));
+                let total = visible
+                    .iter()
+                    .fold(chrono::Duration::zero(), |total, activity| {
+                        total + activity.duration()
+                    });
+                ui.label(format!("Tempo registrado: {}", format_duration(total)));
 
-                if let (Some(first), Some(last)) = (self.activities.first(), self.activities.last())
-                {
-                    ui.label(format!("Primeiro registro: {}", self.format_time(first)));
-                    ui.label(format!("Último registro: {}", self.format_time(last)));
+                if let (Some(first), Some(last)) = (visible.first(), visible.last()) {
+                    ui.label(format!("Primeiro registro: {}", Self::format_time(first)));
+                    ui.label(format!("Último registro: {}", Self::format_time(last)));
                 }
             }
         });
     }
 
-    fn render_settings_content(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+    fn render_stats_content(&mut self, ui: &mut egui::Ui) {
+        if self.stats.is_none()
+            && let Err(e) = self.load_stats()
+        {
+            self.message = Some(format!("Erro ao calcular estatísticas: {e}"));
+        }
+
+        ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Estatísticas").size(18.0).strong());
+                ui.add_space(20.0);
+
+                for period in [StatsPeriod::Day, StatsPeriod::Week, StatsPeriod::Month] {
+                    if ui
+                        .selectable_label(self.stats_period == period, period.label())
+                        .clicked()
+                    {
+                        self.stats_period = period;
+                        self.stats = None;
+                    }
+                }
+            });
+
+            let Some(stats) = &self.stats else {
+                return;
+            };
+
+            ui.label(
+                RichText::new(format!(
+                    "{} a {} (data de referência: {})",
+                    stats.range.start.format("%d/%m/%Y"),
+                    stats.range.end.format("%d/%m/%Y"),
+                    self.selected_date.format("%d/%m/%Y")
+                ))
+                .color(egui::Color32::GRAY),
+            );
+            ui.add_space(10.0);
+
+            if stats.days.is_empty() {
+                ui.label("Nenhuma atividade registrada neste período.");
+                return;
+            }
+
+            let total_seconds: i64 = stats.totals.iter().map(|t| t.total_seconds).sum();
+            ui.label(format!(
+                "Tempo registrado: {}",
+                format_duration(chrono::Duration::seconds(total_seconds))
+            ));
+            ui.label(format!(
+                "Respostas: {} ({} novas atividades, {} continuações)",
+                stats.responses.total(),
+                stats.responses.new_entries,
+                stats.responses.continuations
+            ));
+            ui.add_space(10.0);
+
+            ScrollArea::vertical().show(ui, |ui| {
+                ui.label(RichText::new("Tempo por atividade").size(14.0).strong());
+                egui::Grid::new("stats_totals")
+                    .striped(true)
+                    .num_columns(3)
+                    .show(ui, |ui| {
+                        for total in &stats.totals {
+                            ui.label(&total.description);
+                            ui.label(
+                                RichText::new(format_duration(chrono::Duration::seconds(
+                                    total.total_seconds,
+                                )))
+                                .monospace(),
+                            );
+                            ui.label(format!("{} registro(s)", total.entries));
+                            ui.end_row();
+                        }
+                    });
+
+                ui.add_space(15.0);
+                ui.label(RichText::new("Por dia").size(14.0).strong());
+                egui::Grid::new("stats_days")
+                    .striped(true)
+                    .num_columns(4)
+                    .show(ui, |ui| {
+                        for day in &stats.days {
+                            ui.label(day.date.format("%a %d/%m").to_string());
+                            ui.label(format!("{} registro(s)", day.entries));
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} – {}",
+                                    day.first_start.format("%H:%M"),
+                                    day.last_end.format("%H:%M")
+                                ))
+                                .monospace(),
+                            );
+                            ui.label(format_duration(chrono::Duration::seconds(
+                                day.total_seconds,
+                            )));
+                            ui.end_row();
+                        }
+                    });
+            });
+        });
+    }
+
+    fn render_search_content(&mut self, ui: &mut egui::Ui) {
+        ui.vertical(|ui| {
+            ui.label(RichText::new("Buscar Atividades").size(18.0).strong());
+            ui.add_space(10.0);
+
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.search_query)
+                        .hint_text("palavras, \"frase exata\" ou prefixo*")
+                        .desired_width(350.0),
+                );
+
+                if response.changed() {
+                    self.run_search();
+                }
+
+                if ui.button("Buscar").clicked() {
+                    self.run_search();
+                }
+            });
+
+            ui.add_space(10.0);
+
+            if self.search_query.trim().is_empty() {
+                ui.label("Digite algo para buscar em todas as datas.");
+                return;
+            }
+
+            if self.search_results.is_empty() {
+                ui.label("Nenhuma atividade encontrada.");
+                return;
+            }
+
+            ui.label(
+                RichText::new(format!("{} resultado(s)", self.search_results.len()))
+                    .color(egui::Color32::GRAY),
+            );
+            ui.add_space(5.0);
+
+            let mut selected_date = None;
+
+            ScrollArea::vertical().show(ui, |ui| {
+                for result in &self.search_results {
+                    let activity = &result.activity;
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            let date_label =
+                                activity.timestamp.format("%d/%m/%Y %H:%M").to_string();
+                            if ui
+                                .link(RichText::new(date_label).monospace().strong())
+                                .on_hover_text("Abrir este dia no visualizador")
+                                .clicked()
+                            {
+                                selected_date = Some(activity.date);
+                            }
+
+                            ui.add_space(10.0);
+
+                            ui.vertical(|ui| {
+                                ui.label(RichText::new(&result.snippet).size(13.0));
+                                let mut details = format_duration(activity.duration());
+                                if let Some(project) = &activity.project {
+                                    details.push_str(&format!(" · {}", project.label()));
+                                }
+                                for tag in &activity.tags {
+                                    details.push_str(&format!(" #{tag}"));
+                                }
+                                ui.label(
+                                    RichText::new(details).size(10.0).color(egui::Color32::GRAY),
+                                );
+                            });
+                        });
+                    });
+                    ui.add_space(4.0);
+                }
+            });
+
+            if let Some(date) = selected_date {
+                self.open_date(date);
+            }
+        });
+    }
+
+    fn render_edit_form(ui: &mut egui::Ui, edit: &mut ActivityEdit) -> Option<ActivityAction> {
+        let mut action = None;
+
         ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                ui.label("Início:");
+                ui.add(egui::TextEdit::singleline(&mut edit.timestamp).desired_width(140.0))
+                    .on_hover_text("Formato: AAAA-MM-DD HH:MM");
+                ui.label("Fim:");
+                ui.add(egui::TextEdit::singleline(&mut edit.end_timestamp).desired_width(140.0))
+                    .on_hover_text("Formato: AAAA-MM-DD HH:MM");
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Atividade:");
+                let response =
+                    ui.add(egui::TextEdit::singleline(&mut edit.description).desired_width(300.0));
+
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    action = Some(ActivityAction::SaveEdit);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label("Projeto:");
+                ui.add(egui::TextEdit::singleline(&mut edit.project).desired_width(120.0));
+                ui.label("Tags:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut edit.tags)
+                        .hint_text("foco, reunião")
+                        .desired_width(160.0),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                if ui.button("󰆓 Salvar").clicked() {
+                    action = Some(ActivityAction::SaveEdit);
+                }
+                if ui.button("Cancelar").clicked() {
+                    action = Some(ActivityAction::CancelEdit);
+                }
+            });
+        });
+
+        action
+    }
+
+    fn render_settings_content(&mut self, ctx: &Context, ui: &mut egui::Ui) {
+        ScrollArea::vertical().show(ui, |ui| {
             // Cabeçalho das configurações
             ui.horizontal(|ui| {
                 ui.label(RichText::new("Configurações").size(18.0).strong());
@@ -392,17 +997,24 @@ impl ViewerApp {
                             .color(egui::Color32::from_rgb(255, 165, 0)),
                         );
                     }
+
+                    ui.add_space(10.0);
+                    self.inquiry_options.show(ui);
                 });
             });
 
             ui.add_space(20.0);
 
+            self.schedule_editor.show(ui);
+
+            ui.add_space(20.0);
+
             // Botões de ação
             ui.horizontal(|ui| {
-                if ui.button("💾 Salvar Configurações").clicked() {
-                    if let Err(e) = self.save_config() {
-                        self.message = Some(format!("❌ Erro ao salvar: {e}"));
-                    }
+                if ui.button("💾 Salvar Configurações").clicked()
+                    && let Err(e) = self.save_config()
+                {
+                    self.message = Some(format!("❌ Erro ao salvar: {e}"));
                 }
 
                 ui.add_space(10.0);
@@ -410,11 +1022,60 @@ impl ViewerApp {
                 if ui.button("🔄 Restaurar Padrões").clicked() {
                     let default_config = AppConfig::default();
                     self.selected_interval = default_config.daemon_interval_minutes;
+                    self.inquiry_options = InquiryOptionsEditor::new(&default_config);
+                    self.schedule_editor = ScheduleEditor::new(&ScheduleConfig::default());
                     self.current_theme = Theme::GruvboxDark;
                     self.change_theme(ctx, Theme::GruvboxDark);
                     self.message = Some(" Configurações restauradas para os padrões".to_string());
                 }
             });
+
+            ui.add_space(20.0);
+
+            // Projetos e categorias
+            ui.group(|ui| {
+                ui.vertical(|ui| {
+                    ui.label(RichText::new(" Projetos").size(16.0).strong());
+                    ui.add_space(10.0);
+
+                    if self.projects.is_empty() {
+                        ui.label("Nenhum projeto criado. Use @projeto ao registrar uma atividade.");
+                        return;
+                    }
+
+                    for project in &mut self.projects {
+                        ui.horizontal(|ui| {
+                            let mut rgb = project.rgb().unwrap_or([131, 165, 152]);
+                            if egui::color_picker::color_edit_button_srgb(ui, &mut rgb).changed() {
+                                project.color =
+                                    format!("#{:02x}{:02x}{:02x}", rgb[0], rgb[1], rgb[2]);
+                            }
+
+                            let mut icon = project.icon.clone().unwrap_or_default();
+                            if ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut icon)
+                                        .hint_text("ícone")
+                                        .desired_width(40.0),
+                                )
+                                .changed()
+                            {
+                                project.icon =
+                                    Some(icon.trim().to_string()).filter(|i| !i.is_empty());
+                            }
+
+                            ui.label(&project.name);
+                        });
+                    }
+
+                    ui.add_space(5.0);
+                    if ui.button("󰆓 Salvar Projetos").clicked()
+                        && let Err(e) = self.save_projects()
+                    {
+                        self.message = Some(format!("❌ Erro ao salvar projetos: {e}"));
+                    }
+                });
+            });
         });
     }
 }
@@ -0,0 +1,582 @@
+use anyhow::{Context, Result, bail};
+use chrono::{DateTime, Duration, Local};
+use log::{debug, warn};
+use serde::Serialize;
+use std::thread;
+
+use crate::config::{AppConfig, WebhooksConfig};
+use crate::database::Database;
+use crate::hooks::HookEvent;
+use crate::models::{Activity, WebhookDelivery};
+
+// Espera antes da segunda tentativa; dobra a cada falha, até `MAX_BACKOFF`
+const FIRST_BACKOFF: Duration = Duration::seconds(30);
+const MAX_BACKOFF: Duration = Duration::hours(1);
+
+// Folga da reserva de uma entrega além do tempo limite do POST, para que outro
+// processo não a pegue enquanto ela ainda está sendo enviada
+const LEASE_MARGIN: Duration = Duration::seconds(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::Subcommand)]
+pub enum WebhookAction {
+    /// Mostrar as URLs configuradas e as entregas pendentes
+    Status,
+    /// Entregar agora tudo o que estiver pendente, inclusive o que esgotou as tentativas
+    Flush,
+}
+
+// Corpo do POST: os campos de `Activity`, mais o evento que o gerou
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    event: &'static str,
+    #[serde(flatten)]
+    activity: &'a Activity,
+}
+
+// Nome do evento no corpo e no cabeçalho `X-Acv-Event`. Só atividades
+// registradas e continuadas são enviadas.
+fn event_name(event: HookEvent) -> Option<&'static str> {
+    match event {
+        HookEvent::ActivitySaved => Some("activity_saved"),
+        HookEvent::ActivityContinued => Some("activity_continued"),
+        HookEvent::InquirySkipped | HookEvent::DaemonStart => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Webhooks {
+    config: WebhooksConfig,
+    lease_margin: Duration,
+}
+
+impl Default for Webhooks {
+    fn default() -> Self {
+        Self::new(WebhooksConfig::default())
+    }
+}
+
+impl Webhooks {
+    pub fn new(config: WebhooksConfig) -> Self {
+        Self {
+            config,
+            lease_margin: LEASE_MARGIN,
+        }
+    }
+
+    // Como em `Hooks::load`, uma configuração inválida só desativa o envio
+    pub fn load() -> Self {
+        match AppConfig::load() {
+            Ok(config) => Self::new(config.webhooks),
+            Err(e) => {
+                warn!("⚠️  Webhooks desativados: {e:#}");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.config.urls.is_empty()
+    }
+
+    // Só coloca a atividade na fila de cada URL; o envio, que pode demorar até
+    // o tempo limite por URL, fica com `deliver_pending` ou `deliver_in_background`
+    pub fn send(&self, db: &Database, event: HookEvent, activity: &Activity) {
+        if let Err(e) = self.enqueue(db, event, activity) {
+            warn!("⚠️  {e:#}");
+        }
+    }
+
+    fn enqueue(&self, db: &Database, event: HookEvent, activity: &Activity) -> Result<()> {
+        let Some(name) = event_name(event).filter(|_| self.is_enabled()) else {
+            return Ok(());
+        };
+        let payload = serde_json::to_string(&WebhookPayload {
+            event: name,
+            activity,
+        })
+        .context("Falha ao serializar atividade para o webhook")?;
+
+        for url in &self.config.urls {
+            db.enqueue_webhook(url, name, &payload)?;
+        }
+        Ok(())
+    }
+
+    // Entrega a fila numa thread com conexão própria, sem prender quem chamou.
+    // Um processo que vai terminar logo pode esperar pelo `JoinHandle`.
+    pub fn deliver_in_background(&self) -> Option<thread::JoinHandle<()>> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let webhooks = self.clone();
+        Some(thread::spawn(move || {
+            if let Err(e) = Database::new().and_then(|db| webhooks.deliver_pending(&db, false)) {
+                warn!("⚠️  {e:#}");
+            }
+        }))
+    }
+
+    // Envia as entregas pendentes das URLs configuradas. Com `force`, ignora a
+    // espera entre tentativas e o limite de tentativas. Devolve quantas foram
+    // entregues e quantas falharam.
+    pub fn deliver_pending(&self, db: &Database, force: bool) -> Result<(u32, u32)> {
+        self.deliver_pending_at(db, force, Local::now())
+    }
+
+    // `now` só escolhe as entregas; a reserva, a entrega e a próxima tentativa
+    // usam a hora de cada envio, já que cada POST pode levar até o tempo limite
+    fn deliver_pending_at(
+        &self,
+        db: &Database,
+        force: bool,
+        now: DateTime<Local>,
+    ) -> Result<(u32, u32)> {
+        let (mut delivered, mut failed) = (0, 0);
+        if !self.is_enabled() {
+            return Ok((delivered, failed));
+        }
+
+        let agent = ureq::AgentBuilder::new()
+            .timeout(std::time::Duration::from_secs(self.config.timeout_seconds))
+            .build();
+        // Depois de uma falha, a URL só é tentada de novo na próxima rodada
+        let mut failed_urls: Vec<String> = Vec::new();
+
+        for delivery in db.pending_webhooks(self.config.max_attempts, force, now)? {
+            if !self.config.urls.contains(&delivery.url) || failed_urls.contains(&delivery.url) {
+                continue;
+            }
+
+            let claimed_at = Local::now();
+            let lease = claimed_at
+                + Duration::seconds(self.config.timeout_seconds as i64)
+                + self.lease_margin;
+            if !db.claim_webhook(delivery.id, claimed_at, lease)? {
+                continue;
+            }
+
+            let result = post(&agent, &delivery);
+            let finished_at = Local::now();
+            match result {
+                Ok(()) => {
+                    debug!("🌐 Webhook #{} entregue em {}", delivery.id, delivery.url);
+                    db.webhook_delivered(delivery.id, finished_at)?;
+                    delivered += 1;
+                }
+                Err(e) => {
+                    let retry_at = finished_at + backoff(delivery.attempts + 1);
+                    warn!(
+                        "⚠️  Falha ao entregar webhook #{} (tentativa {}): {e:#}",
+                        delivery.id,
+                        delivery.attempts + 1
+                    );
+                    db.webhook_failed(delivery.id, &format!("{e:#}"), retry_at)?;
+                    failed_urls.push(delivery.url);
+                    failed += 1;
+                }
+            }
+        }
+
+        Ok((delivered, failed))
+    }
+}
+
+fn post(agent: &ureq::Agent, delivery: &WebhookDelivery) -> Result<()> {
+    let result = agent
+        .post(&delivery.url)
+        .set("Content-Type", "application/json")
+        .set("User-Agent", concat!("acv-inq/", env!("CARGO_PKG_VERSION")))
+        .set("X-Acv-Event", &delivery.event)
+        .set("X-Acv-Delivery", &delivery.id.to_string())
+        .send_string(&delivery.payload);
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(ureq::Error::Status(code, response)) => {
+            bail!("HTTP {code} {}", response.status_text())
+        }
+        // A mensagem do ureq já inclui a URL e a causa
+        Err(e) => bail!("{e}"),
+    }
+}
+
+// 30s, 1min, 2min, 4min... até 1h
+fn backoff(attempts: u32) -> Duration {
+    let factor = 2_i32.saturating_pow(attempts.saturating_sub(1).min(16));
+    (FIRST_BACKOFF * factor).min(MAX_BACKOFF)
+}
+
+pub fn run_action(action: WebhookAction) -> Result<()> {
+    let config = AppConfig::load()?;
+    let webhooks = Webhooks::new(config.webhooks.clone());
+    let db = Database::new()?;
+
+    match action {
+        WebhookAction::Status => {
+            if config.webhooks.urls.is_empty() {
+                println!("🌐 Nenhuma URL de webhook configurada ([webhooks] urls no config.toml)");
+            }
+            for url in &config.webhooks.urls {
+                println!("🌐 {url}");
+            }
+
+            let queue = db.webhook_queue(config.webhooks.max_attempts)?;
+            println!("  • Entregas pendentes: {}", queue.pending);
+            if queue.abandoned > 0 {
+                println!(
+                    "  • Entregas que esgotaram as tentativas: {} (use `acv-inq webhooks flush`)",
+                    queue.abandoned
+                );
+            }
+            if let Some(error) = queue.last_error {
+                println!("  • Último erro: {error}");
+            }
+        }
+        WebhookAction::Flush => {
+            if !webhooks.is_enabled() {
+                bail!("Nenhuma URL de webhook configurada ([webhooks] urls no config.toml)");
+            }
+            let (delivered, failed) = webhooks.deliver_pending(&db, true)?;
+            println!("✅ Webhooks entregues: {delivered}");
+            if failed > 0 {
+                bail!("{failed} entrega(s) falharam e serão tentadas de novo");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ActivityInput;
+    use crate::test_support::TestDb;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration as StdDuration;
+
+    // Serviço HTTP local que responde com os status de `statuses`, em ordem (o
+    // último se repete), e guarda o que recebeu
+    struct StandIn {
+        server: Arc<tiny_http::Server>,
+        received: Arc<Mutex<Vec<Received>>>,
+        thread: Option<thread::JoinHandle<()>>,
+    }
+
+    struct Received {
+        event: String,
+        delivery: String,
+        body: String,
+    }
+
+    impl StandIn {
+        fn start(statuses: &[u16]) -> Self {
+            Self::slow(statuses, StdDuration::ZERO)
+        }
+
+        // Como `start`, mas cada resposta demora `delay`
+        fn slow(statuses: &[u16], delay: StdDuration) -> Self {
+            let server = Arc::new(tiny_http::Server::http("127.0.0.1:0").unwrap());
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let mut statuses = statuses.to_vec();
+
+            let thread = thread::spawn({
+                let server = Arc::clone(&server);
+                let received = Arc::clone(&received);
+                move || {
+                    for mut request in server.incoming_requests() {
+                        let header = |name: &'static str| {
+                            request
+                                .headers()
+                                .iter()
+                                .find(|header| header.field.equiv(name))
+                                .map(|header| header.value.to_string())
+                                .unwrap_or_default()
+                        };
+                        let (event, delivery) = (header("X-Acv-Event"), header("X-Acv-Delivery"));
+                        let mut body = String::new();
+                        request.as_reader().read_to_string(&mut body).unwrap();
+                        received.lock().unwrap().push(Received {
+                            event,
+                            delivery,
+                            body,
+                        });
+
+                        let status = if statuses.len() > 1 {
+                            statuses.remove(0)
+                        } else {
+                            statuses[0]
+                        };
+                        thread::sleep(delay);
+                        let _ = request.respond(tiny_http::Response::empty(status));
+                    }
+                }
+            });
+
+            Self {
+                server,
+                received,
+                thread: Some(thread),
+            }
+        }
+
+        fn url(&self) -> String {
+            format!("http://{}/", self.server.server_addr().to_ip().unwrap())
+        }
+
+        fn count(&self) -> usize {
+            self.received.lock().unwrap().len()
+        }
+    }
+
+    impl Drop for StandIn {
+        fn drop(&mut self) {
+            self.server.unblock();
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn webhooks(url: &str, max_attempts: u32) -> Webhooks {
+        Webhooks::new(WebhooksConfig {
+            urls: vec![url.to_string()],
+            timeout_seconds: 5,
+            max_attempts,
+        })
+    }
+
+    fn save_activity(test: &TestDb, webhooks: &Webhooks) -> Activity {
+        let id = test
+            .db
+            .add_activity(&ActivityInput::parse("Revisando #código"))
+            .unwrap();
+        let activity = test.db.get_activity(id).unwrap().unwrap();
+        webhooks.send(&test.db, HookEvent::ActivitySaved, &activity);
+        activity
+    }
+
+    #[test]
+    fn send_only_enqueues() {
+        let stand_in = StandIn::start(&[200]);
+        let test = TestDb::new("webhooks-enqueue");
+        let webhooks = webhooks(&stand_in.url(), 10);
+
+        save_activity(&test, &webhooks);
+
+        assert_eq!(stand_in.count(), 0);
+        assert_eq!(test.db.webhook_queue(10).unwrap().pending, 1);
+    }
+
+    #[test]
+    fn delivers_pending_activity() {
+        let stand_in = StandIn::start(&[200]);
+        let test = TestDb::new("webhooks-delivered");
+        let webhooks = webhooks(&stand_in.url(), 10);
+        let activity = save_activity(&test, &webhooks);
+
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (1, 0));
+
+        let received = stand_in.received.lock().unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].event, "activity_saved");
+        assert!(!received[0].delivery.is_empty());
+        let body: serde_json::Value = serde_json::from_str(&received[0].body).unwrap();
+        assert_eq!(body["event"], "activity_saved");
+        assert_eq!(body["id"], activity.id.unwrap());
+        assert_eq!(body["description"], "Revisando");
+        drop(received);
+
+        // Entregue, não é enviado de novo
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 0));
+        assert_eq!(test.db.webhook_queue(10).unwrap().pending, 0);
+    }
+
+    #[test]
+    fn failed_delivery_waits_with_backoff() {
+        let stand_in = StandIn::start(&[500]);
+        let test = TestDb::new("webhooks-backoff");
+        let webhooks = webhooks(&stand_in.url(), 10);
+        save_activity(&test, &webhooks);
+
+        // A espera conta a partir do fim de cada envio, entre `before` e `after`
+        let before = Local::now();
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 1));
+        let after = Local::now();
+        let queue = test.db.webhook_queue(10).unwrap();
+        assert_eq!(queue.pending, 1);
+        assert!(queue.last_error.unwrap().contains("500"));
+
+        // Primeira espera: 30s
+        let at = before + Duration::seconds(29);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (0, 0)
+        );
+        let before = Local::now();
+        let at = after + Duration::seconds(31);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (0, 1)
+        );
+        let after = Local::now();
+
+        // Segunda espera: o dobro
+        let at = before + Duration::seconds(59);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (0, 0)
+        );
+        let at = after + Duration::seconds(61);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (0, 1)
+        );
+        assert_eq!(stand_in.count(), 3);
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_one_hour() {
+        assert_eq!(backoff(1), Duration::seconds(30));
+        assert_eq!(backoff(2), Duration::minutes(1));
+        assert_eq!(backoff(3), Duration::minutes(2));
+        assert_eq!(backoff(7), Duration::minutes(32));
+        assert_eq!(backoff(8), Duration::hours(1));
+        assert_eq!(backoff(40), Duration::hours(1));
+    }
+
+    #[test]
+    fn retries_after_backoff() {
+        let stand_in = StandIn::start(&[503, 200]);
+        let test = TestDb::new("webhooks-retry");
+        let webhooks = webhooks(&stand_in.url(), 10);
+        save_activity(&test, &webhooks);
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 1));
+        let at = Local::now() + Duration::seconds(31);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (1, 0)
+        );
+
+        // A nova tentativa é a mesma entrega
+        let received = stand_in.received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].delivery, received[1].delivery);
+        assert_eq!(received[0].body, received[1].body);
+        drop(received);
+        assert_eq!(test.db.webhook_queue(10).unwrap().pending, 0);
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let stand_in = StandIn::start(&[500]);
+        let test = TestDb::new("webhooks-give-up");
+        let webhooks = webhooks(&stand_in.url(), 2);
+        save_activity(&test, &webhooks);
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 1));
+        let at = Local::now() + Duration::minutes(1);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (0, 1)
+        );
+
+        let at = Local::now() + Duration::days(1);
+        assert_eq!(
+            webhooks.deliver_pending_at(&test.db, false, at).unwrap(),
+            (0, 0)
+        );
+        assert_eq!(stand_in.count(), 2);
+
+        let queue = test.db.webhook_queue(2).unwrap();
+        assert_eq!((queue.pending, queue.abandoned), (0, 1));
+    }
+
+    #[test]
+    fn flush_delivers_abandoned_and_waiting() {
+        let stand_in = StandIn::start(&[500, 500, 200]);
+        let test = TestDb::new("webhooks-flush");
+        let webhooks = webhooks(&stand_in.url(), 1);
+        save_activity(&test, &webhooks);
+
+        // Esgotou a única tentativa
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 1));
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 0));
+
+        // Com force, tenta mesmo dentro da espera e sem tentativas restantes
+        assert_eq!(webhooks.deliver_pending(&test.db, true).unwrap(), (0, 1));
+        assert_eq!(webhooks.deliver_pending(&test.db, true).unwrap(), (1, 0));
+        assert_eq!(stand_in.count(), 3);
+
+        let queue = test.db.webhook_queue(1).unwrap();
+        assert_eq!((queue.pending, queue.abandoned), (0, 0));
+    }
+
+    #[test]
+    fn flush_skips_delivery_reserved_by_another_process() {
+        let stand_in = StandIn::start(&[200]);
+        let test = TestDb::new("webhooks-lease");
+        let webhooks = webhooks(&stand_in.url(), 10);
+        save_activity(&test, &webhooks);
+        let now = Local::now();
+
+        // Outro processo reservou a entrega e ainda está enviando
+        let delivery = &test.db.pending_webhooks(10, false, now).unwrap()[0];
+        assert!(
+            test.db
+                .claim_webhook(delivery.id, now, now + Duration::seconds(40))
+                .unwrap()
+        );
+
+        assert_eq!(webhooks.deliver_pending(&test.db, true).unwrap(), (0, 0));
+        assert_eq!(stand_in.count(), 0);
+    }
+
+    #[test]
+    fn resumes_expired_reservation() {
+        let stand_in = StandIn::start(&[200]);
+        let test = TestDb::new("webhooks-expired-lease");
+        let webhooks = webhooks(&stand_in.url(), 10);
+        save_activity(&test, &webhooks);
+        let now = Local::now();
+
+        // Processo encerrado no meio do envio: a reserva venceu
+        let delivery = &test.db.pending_webhooks(10, false, now).unwrap()[0];
+        assert!(
+            test.db
+                .claim_webhook(delivery.id, now, now - Duration::seconds(1))
+                .unwrap()
+        );
+
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (1, 0));
+        assert_eq!(stand_in.count(), 1);
+    }
+
+    #[test]
+    fn slow_batch_keeps_each_delivery_reserved_while_it_is_sent() {
+        // Cada POST leva mais que a folga da reserva
+        let stand_in = StandIn::slow(&[200], StdDuration::from_millis(1500));
+        let test = TestDb::new("webhooks-slow");
+        let mut webhooks = webhooks(&stand_in.url(), 10);
+        webhooks.config.timeout_seconds = 2;
+        webhooks.lease_margin = Duration::zero();
+        save_activity(&test, &webhooks);
+        save_activity(&test, &webhooks);
+
+        let batch = thread::spawn({
+            let webhooks = webhooks.clone();
+            let path = test.path();
+            move || {
+                let db = Database::open(&path).unwrap();
+                webhooks.deliver_pending(&db, false).unwrap()
+            }
+        });
+
+        // A segunda entrega está sendo enviada; uma reserva calculada no
+        // início do lote já teria vencido
+        thread::sleep(StdDuration::from_millis(2500));
+        assert_eq!(webhooks.deliver_pending(&test.db, false).unwrap(), (0, 0));
+
+        assert_eq!(batch.join().unwrap(), (2, 0));
+        assert_eq!(stand_in.count(), 2);
+    }
+}